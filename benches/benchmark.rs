@@ -7,11 +7,11 @@ fn criterion_benchmark(c: &mut Criterion) {
         b.iter(|| FloatLiteral::from_bytes(black_box(b"0xabc.defp123")));
     });
     c.bench_function("convert f64", |b| {
-        let literal = FloatLiteral::from_bytes(black_box(b"0xabc.defp123")).unwrap();
+        let (literal, _) = FloatLiteral::from_bytes(black_box(b"0xabc.defp123")).unwrap();
         b.iter(move || literal.clone().convert::<f64>())
     });
     c.bench_function("convert f32", |b| {
-        let literal = FloatLiteral::from_bytes(black_box(b"0xabc.defp123")).unwrap();
+        let (literal, _) = FloatLiteral::from_bytes(black_box(b"0xabc.defp123")).unwrap();
         b.iter(move || literal.clone().convert::<f32>())
     });
 }
@@ -0,0 +1,488 @@
+//! Exact decimal expansions of literals.
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec, vec::Vec};
+
+use crate::descriptor::RoundingInterval;
+use crate::exact::{BigUint, Dyadic};
+use crate::rounding::{RoundingDirection, RoundingMode};
+use crate::FloatLiteral;
+use core::cmp::Ordering;
+use core::fmt::Write;
+
+/// Powers of five up to this are computed exactly by `from_dyadic_to_place`.
+/// Larger ones are bounded instead. Each power of five has about 2.3 bits.
+const MAX_EXACT_POWER: u64 = 1024;
+
+/// Get `5^power`.
+fn power_of_five(power: u64) -> BigUint {
+    let mut result = BigUint::from_u128(1);
+    for _ in 0..power / 13 {
+        result = result.mul_small(5u32.pow(13));
+    }
+    result.mul_small(5u32.pow((power % 13) as u32))
+}
+
+/// Get a lower and an upper bound on `5^power`, with `precision` bits each.
+fn power_of_five_bounds(power: u64, precision: u64) -> (Dyadic, Dyadic) {
+    let round = |value: Dyadic, mode| {
+        let quantum = value.exponent + value.mantissa.bit_length() as i64 - precision as i64;
+        if quantum <= value.exponent {
+            return value;
+        }
+        let (mantissa, _) = value.round_to_quantum(quantum, mode);
+        Dyadic {
+            is_positive: true,
+            mantissa,
+            exponent: quantum,
+        }
+    };
+    let one = Dyadic {
+        is_positive: true,
+        mantissa: BigUint::from_u128(1),
+        exponent: 0,
+    };
+    let (mut low, mut high) = (one.clone(), one);
+    // Square and multiply, from the top bit of the power down. Rounding each
+    // step in the same direction keeps the bounds on either side.
+    for bit in (0..64 - power.leading_zeros()).rev() {
+        low = round(low.mul(&low), RoundingMode::TowardZero);
+        high = round(high.mul(&high), RoundingMode::TowardPositive);
+        if (power >> bit) & 1 == 1 {
+            low.mantissa = low.mantissa.mul_small(5);
+            high.mantissa = high.mantissa.mul_small(5);
+        }
+    }
+    (low, high)
+}
+
+/// Estimate `floor(log10(|value|))` for a non-zero value. The estimate is never
+/// above it, and at most 3 below it.
+fn decimal_exponent_estimate(value: &Dyadic) -> i64 {
+    // The value is at least `2^(bits - 1)` and less than `2^bits`.
+    let bits = value.exponent + value.mantissa.bit_length() as i64;
+    // 1292913986 / 2^32 is just below log10(2).
+    let estimate = ((bits - 1) as i128 * 1_292_913_986) >> 32;
+    estimate as i64 - 1
+}
+
+/// Get `floor(|value| / 10^place)`, and whether anything was cut off, with
+/// `power` in place of `5^|place|`.
+fn scale(value: &Dyadic, place: i64, power: &Dyadic) -> (BigUint, bool) {
+    if place <= 0 {
+        // Multiply by `10^-place`, which is `5^-place * 2^-place`.
+        let scaled = Dyadic {
+            is_positive: true,
+            mantissa: value.mantissa.mul(&power.mantissa),
+            exponent: value.exponent + power.exponent - place,
+        };
+        let (integer, direction) = scaled.round_to_quantum(0, RoundingMode::TowardZero);
+        (integer, direction != RoundingDirection::Exact)
+    } else {
+        // Divide by `10^place`, which is `5^place * 2^place`.
+        let shift = value.exponent - place - power.exponent;
+        let (integer, remainder) = if shift >= 0 {
+            value.mantissa.shl(shift as u64).div_rem(&power.mantissa)
+        } else {
+            let divisor = power.mantissa.shl((-shift) as u64);
+            value.mantissa.div_rem(&divisor)
+        };
+        (integer, !remainder.is_zero())
+    }
+}
+
+/// The exact decimal value of a literal, as `0.digits * 10^decimal_offset`.
+/// This mirrors the hexadecimal digits in `FloatLiteral`.
+pub(crate) struct Decimal {
+    pub(crate) is_positive: bool,
+    /// The values of the digits, without leading or trailing zeros. Zero has no
+    /// digits.
+    pub(crate) digits: Vec<u8>,
+    pub(crate) decimal_offset: i64,
+}
+
+impl Decimal {
+    pub(crate) fn from_literal(literal: &FloatLiteral) -> Decimal {
+        Decimal::from_dyadic(&Dyadic::from_literal(literal))
+    }
+
+    pub(crate) fn from_dyadic(value: &Dyadic) -> Decimal {
+        let exact = value.normalize();
+        // A binary fraction with k bits after the point has exactly k decimal
+        // digits after the point, because 2^-k = 5^k / 10^k.
+        let (integer, fraction_digits) = if exact.exponent >= 0 {
+            (exact.mantissa.shl(exact.exponent as u64), 0)
+        } else {
+            let power = power_of_five((-exact.exponent) as u64);
+            (exact.mantissa.mul(&power), exact.exponent)
+        };
+
+        let mut digits = integer.to_decimal_digits();
+        let decimal_offset = digits.len() as i64 + fraction_digits;
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+        Decimal {
+            is_positive: exact.is_positive,
+            digits,
+            decimal_offset,
+        }
+    }
+
+    /// Get the digits of a value down to the place value `10^place`. If any
+    /// non-zero digits below that are cut off, a 1 is added in the next place
+    /// instead, so rounding to a place above it gives the same result as
+    /// rounding the exact value.
+    ///
+    /// Unlike `from_dyadic`, this only takes time proportional to the number of
+    /// digits that are kept, except in the rare cases where the value is too
+    /// close to a multiple of `10^place` to tell from bounds on it.
+    pub(crate) fn from_dyadic_to_place(value: &Dyadic, place: i64) -> Decimal {
+        let is_positive = value.is_positive;
+        if value.mantissa.is_zero() {
+            return Decimal {
+                is_positive,
+                digits: Vec::new(),
+                decimal_offset: 0,
+            };
+        }
+
+        // Values below `10^place` only have the digit that was cut off.
+        let estimate = decimal_exponent_estimate(value);
+        if estimate + 4 <= place {
+            return Decimal {
+                is_positive,
+                digits: vec![1],
+                decimal_offset: place,
+            };
+        }
+
+        // Past the exact powers of five, bounds on the power are usually
+        // enough to get the same integer part from both of them.
+        let power = if place < 0 {
+            (-place) as u64
+        } else {
+            place as u64
+        };
+        let bounded = if power <= MAX_EXACT_POWER {
+            None
+        } else {
+            let precision = 4 * (estimate + 4 - place) as u64 + 64;
+            let (low, high) = power_of_five_bounds(power, precision);
+            let (low, high) = (scale(value, place, &low), scale(value, place, &high));
+            // Dividing by a larger power gives a smaller result.
+            let (low, high) = if place <= 0 { (low, high) } else { (high, low) };
+            if low.0 == high.0 && low.1 {
+                Some(low)
+            } else {
+                None
+            }
+        };
+        let (integer, cut_off) = bounded.unwrap_or_else(|| {
+            let exact = Dyadic {
+                is_positive: true,
+                mantissa: power_of_five(power),
+                exponent: 0,
+            };
+            scale(value, place, &exact)
+        });
+
+        let mut digits = integer.to_decimal_digits();
+        let decimal_offset = digits.len() as i64 + place;
+        if cut_off {
+            digits.push(1);
+        } else {
+            while digits.last() == Some(&0) {
+                digits.pop();
+            }
+        }
+        Decimal {
+            is_positive,
+            digits,
+            decimal_offset,
+        }
+    }
+
+    /// Get enough digits of a value to round it to `precision` digits after
+    /// the first one, like `to_exponential` does.
+    pub(crate) fn from_dyadic_with_precision(value: &Dyadic, precision: usize) -> Decimal {
+        if value.mantissa.is_zero() {
+            return Decimal::from_dyadic(value);
+        }
+        // The estimate is never above the exponent of the first digit, so this
+        // keeps at least one digit after the last one that is rounded to.
+        let place = decimal_exponent_estimate(value) - precision as i64 - 1;
+        Decimal::from_dyadic_to_place(value, place)
+    }
+
+    /// Parse a decimal number, like `-1.25e-3`. Returns `None` if the string
+    /// isn't a decimal number.
+    pub(crate) fn parse(s: &str) -> Option<Decimal> {
+        let (is_positive, s) = match s.as_bytes().first() {
+            Some(b'-') => (false, &s[1..]),
+            Some(b'+') => (true, &s[1..]),
+            _ => (true, s),
+        };
+        let (significand, exponent) = match s.find(|c: char| c.eq_ignore_ascii_case(&'e')) {
+            Some(index) => (&s[..index], s[index + 1..].parse::<i64>().ok()?),
+            None => (s, 0),
+        };
+        let (integer_part, fraction_part) = match significand.find('.') {
+            Some(index) => (&significand[..index], &significand[index + 1..]),
+            None => (significand, ""),
+        };
+        if integer_part.is_empty() && fraction_part.is_empty() {
+            return None;
+        }
+
+        let mut digits = Vec::with_capacity(integer_part.len() + fraction_part.len());
+        for c in integer_part.chars().chain(fraction_part.chars()) {
+            digits.push(c.to_digit(10)? as u8);
+        }
+        let first_digit = digits.iter().position(|&d| d != 0).unwrap_or(digits.len());
+        digits.drain(..first_digit);
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+        let decimal_offset = if digits.is_empty() {
+            0
+        } else {
+            (integer_part.len() as i64 - first_digit as i64).checked_add(exponent)?
+        };
+        Some(Decimal {
+            is_positive,
+            digits,
+            decimal_offset,
+        })
+    }
+
+    /// Find the decimal with the fewest digits whose magnitude is in the
+    /// interval. If there is more than one, find the one closest to the value
+    /// of the interval, with ties away from zero.
+    pub(crate) fn shortest(interval: &RoundingInterval) -> Decimal {
+        let exact = Decimal::from_dyadic(&interval.value);
+        if exact.digits.is_empty() {
+            return exact;
+        }
+        let low = Decimal::from_dyadic(&interval.low);
+        let high = interval.high.as_ref().map(Decimal::from_dyadic);
+        let contains = |decimal: &Decimal| decimal.is_between(&low, high.as_ref(), interval);
+
+        // The nearest decimal with a given number of digits is the best one,
+        // unless it falls outside the interval and the other neighbor doesn't.
+        for length in 1.. {
+            let place = exact.decimal_offset - length;
+            let nearest = exact.round_to_place(place, RoundingMode::NearestAway);
+            if contains(&nearest) {
+                return nearest;
+            }
+            let down = exact.round_to_place(place, RoundingMode::TowardZero);
+            let other = if nearest.cmp_magnitude(&down) == Ordering::Equal {
+                let away_from_zero = if exact.is_positive {
+                    RoundingMode::TowardPositive
+                } else {
+                    RoundingMode::TowardNegative
+                };
+                exact.round_to_place(place, away_from_zero)
+            } else {
+                down
+            };
+            if contains(&other) {
+                return other;
+            }
+        }
+        unreachable!("the exact value is always in the interval")
+    }
+
+    /// Check whether the magnitude is in the interval.
+    pub(crate) fn is_in(&self, interval: &RoundingInterval) -> bool {
+        let low = Decimal::from_dyadic(&interval.low);
+        let high = interval.high.as_ref().map(Decimal::from_dyadic);
+        self.is_between(&low, high.as_ref(), interval)
+    }
+
+    /// Check whether the magnitude is between `low` and `high`, which are the
+    /// ends of `interval` as decimals. There is no upper limit if `high` is
+    /// `None`.
+    fn is_between(
+        &self,
+        low: &Decimal,
+        high: Option<&Decimal>,
+        interval: &RoundingInterval,
+    ) -> bool {
+        let above_low = match self.cmp_magnitude(low) {
+            Ordering::Greater => true,
+            Ordering::Equal => interval.low_inclusive,
+            Ordering::Less => false,
+        };
+        let below_high = match high.map(|high| self.cmp_magnitude(high)) {
+            None | Some(Ordering::Less) => true,
+            Some(Ordering::Equal) => interval.high_inclusive,
+            Some(Ordering::Greater) => false,
+        };
+        above_low && below_high
+    }
+
+    /// Get the number of significant digits.
+    pub(crate) fn len(&self) -> usize {
+        self.digits.len()
+    }
+
+    /// Compare the magnitudes of two decimals.
+    fn cmp_magnitude(&self, other: &Decimal) -> Ordering {
+        match (self.digits.is_empty(), other.digits.is_empty()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            (false, false) => self
+                .decimal_offset
+                .cmp(&other.decimal_offset)
+                .then_with(|| self.digits.cmp(&other.digits)),
+        }
+    }
+
+    /// Get the digit with place value `10^place`.
+    fn digit(&self, place: i64) -> u8 {
+        let index = self.decimal_offset - 1 - place;
+        if index < 0 {
+            0
+        } else {
+            self.digits.get(index as usize).copied().unwrap_or(0)
+        }
+    }
+
+    /// Round to a multiple of `10^place`.
+    pub(crate) fn round_to_place(&self, place: i64, mode: RoundingMode) -> Decimal {
+        let keep = self.decimal_offset - place;
+        if keep >= self.digits.len() as i64 || self.digits.is_empty() {
+            return Decimal {
+                is_positive: self.is_positive,
+                digits: self.digits.clone(),
+                decimal_offset: self.decimal_offset,
+            };
+        }
+
+        // The first dropped digit decides whether we are past halfway, and
+        // any other non-zero digits put us strictly past or before it.
+        let dropped = &self.digits[keep.max(0) as usize..];
+        let first_dropped = if keep >= 0 { dropped[0] } else { 0 };
+        let round = first_dropped >= 5;
+        let sticky = first_dropped % 5 != 0
+            || if keep >= 0 {
+                dropped[1..].iter().any(|&d| d != 0)
+            } else {
+                true
+            };
+
+        let mut digits: Vec<u8> = self.digits[..keep.max(0) as usize].to_vec();
+        let mut decimal_offset = self.decimal_offset;
+        let odd = matches!(digits.last(), Some(d) if d % 2 == 1);
+        if mode.rounds_up(self.is_positive, odd, round, sticky) {
+            // Add one in the last place, carrying through any nines.
+            while digits.last() == Some(&9) {
+                digits.pop();
+            }
+            match digits.last_mut() {
+                Some(digit) => *digit += 1,
+                None => {
+                    digits.push(1);
+                    decimal_offset = self.decimal_offset.max(place) + 1;
+                }
+            }
+        }
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+        if digits.is_empty() {
+            decimal_offset = 0;
+        }
+        Decimal {
+            is_positive: self.is_positive,
+            digits,
+            decimal_offset,
+        }
+    }
+
+    fn push_sign(&self, output: &mut String) {
+        if !self.is_positive {
+            output.push('-');
+        }
+    }
+
+    fn push_digits(&self, output: &mut String, places: impl Iterator<Item = i64>) {
+        output.extend(places.map(|place| (b'0' + self.digit(place)) as char));
+    }
+
+    /// Write all of the digits, with as few leading and trailing zeros as
+    /// possible.
+    pub(crate) fn to_positional(&self) -> String {
+        let mut output = String::new();
+        self.push_sign(&mut output);
+        let lowest_place = self.decimal_offset - self.digits.len() as i64;
+        self.push_digits(&mut output, (0..self.decimal_offset.max(1)).rev());
+        if lowest_place < 0 {
+            output.push('.');
+            self.push_digits(&mut output, (lowest_place..0).rev());
+        }
+        output
+    }
+
+    /// Write the digits in scientific notation, like the `LowerExp` formatting
+    /// of `f64`.
+    pub(crate) fn to_scientific(&self) -> String {
+        let mut output = String::new();
+        self.push_sign(&mut output);
+        let exponent = if self.digits.is_empty() {
+            0
+        } else {
+            self.decimal_offset - 1
+        };
+        self.push_digits(&mut output, exponent..=exponent);
+        if self.digits.len() > 1 {
+            output.push('.');
+            let lowest_place = self.decimal_offset - self.digits.len() as i64;
+            self.push_digits(&mut output, (lowest_place..exponent).rev());
+        }
+        write!(output, "e{}", exponent).unwrap();
+        output
+    }
+
+    /// Format like `printf("%.*e")`.
+    pub(crate) fn to_exponential(&self, precision: usize, mode: RoundingMode) -> String {
+        let mut output = String::new();
+        self.push_sign(&mut output);
+        let place = self.decimal_offset - 1 - precision as i64;
+        let rounded = self.round_to_place(place, mode);
+        // Zero has an exponent of zero.
+        let exponent = if rounded.digits.is_empty() {
+            0
+        } else {
+            rounded.decimal_offset - 1
+        };
+        rounded.push_digits(&mut output, exponent..=exponent);
+        if precision > 0 {
+            output.push('.');
+            rounded.push_digits(&mut output, (exponent - precision as i64..exponent).rev());
+        }
+        let (sign, magnitude) = if exponent < 0 {
+            ('-', -exponent)
+        } else {
+            ('+', exponent)
+        };
+        write!(output, "e{}{:02}", sign, magnitude).unwrap();
+        output
+    }
+
+    /// Format like `printf("%.*f")`.
+    pub(crate) fn to_fixed(&self, precision: usize, mode: RoundingMode) -> String {
+        let mut output = String::new();
+        self.push_sign(&mut output);
+        let rounded = self.round_to_place(-(precision as i64), mode);
+        rounded.push_digits(&mut output, (0..rounded.decimal_offset.max(1)).rev());
+        if precision > 0 {
+            output.push('.');
+            rounded.push_digits(&mut output, (-(precision as i64)..0).rev());
+        }
+        output
+    }
+}
@@ -0,0 +1,506 @@
+//! Binary floating point layouts, and rounding literals to them.
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use crate::exact::{BigUint, Dyadic};
+use crate::rounding::{Normalized, RoundingDirection, RoundingMode};
+use crate::{Conversion, ConversionFlags, FloatLiteral, RoundingError};
+
+/// An unsigned integer that holds a mantissa or an encoded number. Formats
+/// that fit in 128 bits use `u128`, and wider ones use `BigUint`.
+pub(crate) trait Mantissa: Clone + Ord {
+    fn from_u128(value: u128) -> Self;
+    fn bit_length(&self) -> u64;
+    fn shl(&self, bits: u64) -> Self;
+    fn shr(&self, bits: u64) -> Self;
+    fn add(&self, other: &Self) -> Self;
+    fn sub(&self, other: &Self) -> Self;
+}
+
+impl Mantissa for u128 {
+    fn from_u128(value: u128) -> u128 {
+        value
+    }
+
+    fn bit_length(&self) -> u64 {
+        128 - self.leading_zeros() as u64
+    }
+
+    fn shl(&self, bits: u64) -> u128 {
+        self << bits
+    }
+
+    fn shr(&self, bits: u64) -> u128 {
+        self >> bits
+    }
+
+    fn add(&self, other: &u128) -> u128 {
+        self + other
+    }
+
+    fn sub(&self, other: &u128) -> u128 {
+        self - other
+    }
+}
+
+impl Mantissa for BigUint {
+    fn from_u128(value: u128) -> BigUint {
+        BigUint::from_u128(value)
+    }
+
+    fn bit_length(&self) -> u64 {
+        BigUint::bit_length(self)
+    }
+
+    fn shl(&self, bits: u64) -> BigUint {
+        BigUint::shl(self, bits)
+    }
+
+    fn shr(&self, bits: u64) -> BigUint {
+        BigUint::shr(self, bits)
+    }
+
+    fn add(&self, other: &BigUint) -> BigUint {
+        BigUint::add(self, other)
+    }
+
+    fn sub(&self, other: &BigUint) -> BigUint {
+        BigUint::sub(self, other)
+    }
+}
+
+/// The exact value of a non-zero literal, ready to be rounded to a mantissa.
+trait Exact<M> {
+    /// The binary exponent of the leading one.
+    fn exponent(&self) -> i64;
+
+    /// Round the magnitude to an integer multiple of `2^quantum`. Returns the
+    /// multiple and the direction it was rounded in.
+    fn round_to_quantum(&self, quantum: i64, mode: RoundingMode) -> (M, RoundingDirection);
+}
+
+/// The leading 128 bits are enough to round to any format that fits in 128
+/// bits.
+impl Exact<u128> for Normalized {
+    fn exponent(&self) -> i64 {
+        self.exponent
+    }
+
+    fn round_to_quantum(&self, quantum: i64, mode: RoundingMode) -> (u128, RoundingDirection) {
+        Normalized::round_to_quantum(self, quantum, mode)
+            .expect("a rounded mantissa always fits in a u128")
+    }
+}
+
+impl Exact<BigUint> for Dyadic {
+    fn exponent(&self) -> i64 {
+        self.exponent + self.mantissa.bit_length() as i64 - 1
+    }
+
+    fn round_to_quantum(&self, quantum: i64, mode: RoundingMode) -> (BigUint, RoundingDirection) {
+        Dyadic::round_to_quantum(self, quantum, mode)
+    }
+}
+
+/// A literal rounded to a binary format, before it is encoded.
+pub(crate) enum Rounded<M> {
+    /// The magnitude is `mantissa * 2^quantum`, where `2^quantum` is the
+    /// spacing between values of the format at that magnitude. Normal numbers
+    /// have a mantissa of exactly `mantissa_bits + 1` bits.
+    Finite {
+        mantissa: M,
+        quantum: i64,
+    },
+    Infinite,
+}
+
+/// The magnitudes that round to the same value, rounding to nearest, ties to
+/// even.
+pub(crate) struct RoundingInterval {
+    /// The lower end.
+    pub(crate) low: Dyadic,
+    /// The value that the interval rounds to.
+    pub(crate) value: Dyadic,
+    /// The upper end, or `None` if everything above saturates to the value.
+    pub(crate) high: Option<Dyadic>,
+    /// Whether `low` rounds to the value.
+    pub(crate) low_inclusive: bool,
+    /// Whether `high` rounds to the value.
+    pub(crate) high_inclusive: bool,
+}
+
+/// The layout of a binary floating point format, described at runtime.
+///
+/// Numbers are encoded as a sign bit, then the biased exponent, then the
+/// mantissa. This covers the IEEE 754 interchange formats of any width, as
+/// well as most minifloats.
+///
+/// ```rust
+/// use hexponent::{FloatLiteral, FormatDescriptor, RoundingMode};
+/// // TensorFloat-32 has the exponent range of an f32 and the precision of an
+/// // f16, stored in 19 bits.
+/// let tf32 = FormatDescriptor::ieee(8, 10);
+/// let literal: FloatLiteral = "-0x1.8p1".parse().unwrap();
+/// let conversion = tf32.convert(&literal, RoundingMode::NearestEven);
+/// assert_eq!(conversion.value, [0x00, 0x02, 0x06]);
+///
+/// // Other layouts can be built up from an IEEE 754 style one.
+/// let e4m3 = FormatDescriptor {
+///     has_infinity: false,
+///     ..FormatDescriptor::ieee(4, 3)
+/// };
+/// let literal: FloatLiteral = "0x1p20".parse().unwrap();
+/// let conversion = e4m3.convert(&literal, RoundingMode::NearestEven);
+/// assert_eq!(conversion.value, [0x7e]);
+/// assert!(conversion.flags.overflow);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FormatDescriptor {
+    /// The number of bits in the exponent field, from 1 to 32.
+    pub exponent_bits: u32,
+    /// The number of bits after the binary point. This doesn't include the
+    /// leading bit, whether it is stored or not.
+    pub mantissa_bits: u32,
+    /// The exponent bias. Normal numbers are `1.mantissa * 2^(exponent - bias)`.
+    pub exponent_bias: i64,
+    /// Whether the smallest exponent is used for subnormal numbers. If it
+    /// isn't, it is only used for zero, and numbers that are too small round
+    /// to either zero or the smallest normal number.
+    pub has_subnormals: bool,
+    /// Whether the largest exponent is reserved for infinities and NaNs. If it
+    /// isn't, numbers that are too large saturate to the largest finite value.
+    pub has_infinity: bool,
+    /// Whether there is a NaN encoding. Without infinities, the NaN is either
+    /// the bits of negative zero, or all ones if there is a negative zero.
+    pub has_nan: bool,
+    /// Whether there is a negative zero. Without one, zeros are always
+    /// positive.
+    pub has_signed_zero: bool,
+    /// Whether the leading bit of the mantissa is stored, like in the x87
+    /// extended format.
+    pub explicit_integer_bit: bool,
+}
+
+impl FormatDescriptor {
+    /// Describe an IEEE 754 style format, with the usual exponent bias,
+    /// subnormal numbers, infinities, NaNs and signed zeros.
+    ///
+    /// `exponent_bits` must be between 1 and 32, like for the conversions.
+    /// This isn't checked here: outside that range, calculating the bias
+    /// overflows, which panics in debug builds and gives a meaningless bias
+    /// otherwise.
+    pub const fn ieee(exponent_bits: u32, mantissa_bits: u32) -> FormatDescriptor {
+        FormatDescriptor {
+            exponent_bits,
+            mantissa_bits,
+            exponent_bias: (1 << (exponent_bits - 1)) - 1,
+            has_subnormals: true,
+            has_infinity: true,
+            has_nan: true,
+            has_signed_zero: true,
+            explicit_integer_bit: false,
+        }
+    }
+
+    /// The total number of bits in the format, including the sign.
+    pub fn total_bits(&self) -> u32 {
+        1 + self.exponent_bits + self.field_bits()
+    }
+
+    /// The number of bits in the mantissa field.
+    fn field_bits(&self) -> u32 {
+        self.mantissa_bits + self.explicit_integer_bit as u32
+    }
+
+    /// The exponent of the largest finite numbers.
+    pub(crate) fn max_exponent(&self) -> i64 {
+        let max_biased_exponent = (1 << self.exponent_bits) - 1 - self.has_infinity as i64;
+        max_biased_exponent - self.exponent_bias
+    }
+
+    /// The mantissa of the largest finite number, including the leading one.
+    fn max_mantissa<M: Mantissa>(&self) -> M {
+        // Without infinities or NaN in the largest exponent, a NaN of all ones
+        // takes the place of the largest mantissa.
+        let nan_is_all_ones = !self.has_infinity && self.has_nan && self.has_signed_zero;
+        M::from_u128(1)
+            .shl(self.mantissa_bits as u64 + 1)
+            .sub(&M::from_u128(1 + nan_is_all_ones as u128))
+    }
+
+    /// The exponent of the smallest normal numbers.
+    fn min_exponent(&self) -> i64 {
+        1 - self.exponent_bias
+    }
+
+    /// Check that the exponent field has a supported width.
+    pub(crate) fn check_exponent_bits(&self) {
+        assert!(
+            self.exponent_bits >= 1 && self.exponent_bits <= 32,
+            "the exponent must have between 1 and 32 bits"
+        );
+    }
+
+    /// Convert a literal to this format with the given rounding mode. The
+    /// encoded number is returned as little endian bytes, with any unused bits
+    /// in the last byte set to zero.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `exponent_bits` is not between 1 and 32.
+    pub fn convert(&self, literal: &FloatLiteral, mode: RoundingMode) -> Conversion<Vec<u8>> {
+        self.check_exponent_bits();
+        let length = (self.total_bits() as usize + 7) / 8;
+        let value = Dyadic::from_literal(literal);
+        let value = if value.mantissa.is_zero() {
+            None
+        } else {
+            Some(value)
+        };
+        self.round(literal, value, mode).map(|rounded| {
+            self.encode::<BigUint>(literal.is_positive, rounded)
+                .to_le_bytes(length)
+        })
+    }
+
+    /// Round a literal to this format, without encoding it. `value` is the
+    /// exact value of the literal, or `None` if it is zero.
+    fn round<M: Mantissa, E: Exact<M>>(
+        &self,
+        literal: &FloatLiteral,
+        value: Option<E>,
+        mode: RoundingMode,
+    ) -> Conversion<Rounded<M>> {
+        let mantissa_bits = self.mantissa_bits as i64;
+        let min_exponent = self.min_exponent();
+
+        // If there were all 0s, return 0 or -0.
+        let value = match value {
+            Some(value) => value,
+            None => {
+                return Conversion::exact(Rounded::Finite {
+                    mantissa: M::from_u128(0),
+                    quantum: min_exponent - mantissa_bits,
+                })
+            }
+        };
+
+        let mut flags = ConversionFlags::default();
+        let exponent = value.exponent();
+
+        // Tininess after rounding is decided by rounding as if the exponent
+        // range were unbounded, so it needs a separate rounding step.
+        if exponent < min_exponent {
+            flags.tiny_before_rounding = true;
+            let (mantissa, _) = value.round_to_quantum(exponent - mantissa_bits, mode);
+            let carry = mantissa.bit_length() as i64 - (mantissa_bits + 1);
+            flags.tiny_after_rounding = exponent + carry < min_exponent;
+        }
+
+        // Keep `mantissa_bits` bits after the leading one. Subnormal numbers
+        // keep fewer bits because they can't use an exponent below the minimum.
+        // Without subnormals, tiny numbers round to a multiple of the smallest
+        // normal number.
+        let mut quantum = if exponent >= min_exponent {
+            exponent - mantissa_bits
+        } else if self.has_subnormals {
+            min_exponent - mantissa_bits
+        } else {
+            min_exponent
+        };
+        let (mut mantissa, mut direction) = value.round_to_quantum(quantum, mode);
+        if mantissa.bit_length() != 0 && !self.has_subnormals && exponent < min_exponent {
+            mantissa = mantissa.shl(self.mantissa_bits as u64);
+            quantum -= mantissa_bits;
+        }
+
+        // Rounding up can carry into the next power of two.
+        if mantissa.bit_length() as i64 > mantissa_bits + 1 {
+            mantissa = mantissa.shr(1);
+            quantum += 1;
+        }
+
+        // Check for overflows. Depending on the rounding mode, these either go
+        // to infinity or stop at the largest finite number.
+        let max_quantum = self.max_exponent() - mantissa_bits;
+        let rounded = if quantum > max_quantum
+            || (quantum == max_quantum && mantissa > self.max_mantissa())
+        {
+            flags.overflow = true;
+            if self.has_infinity && mode.overflows_to_infinity(literal.is_positive) {
+                direction = RoundingDirection::Up;
+                Rounded::Infinite
+            } else {
+                direction = RoundingDirection::Down;
+                Rounded::Finite {
+                    mantissa: self.max_mantissa(),
+                    quantum: max_quantum,
+                }
+            }
+        } else {
+            Rounded::Finite { mantissa, quantum }
+        };
+
+        flags.inexact = direction != RoundingDirection::Exact;
+        flags.underflow = flags.tiny_after_rounding && flags.inexact;
+
+        Conversion {
+            value: rounded,
+            flags,
+            direction,
+        }
+    }
+
+    /// Round a literal to this format using its leading 128 bits. This only
+    /// works for formats that fit in 128 bits.
+    fn round_u128(&self, literal: &FloatLiteral, mode: RoundingMode) -> Conversion<Rounded<u128>> {
+        self.round(literal, Normalized::new(literal), mode)
+    }
+
+    /// Encode a rounded value as bits.
+    fn encode<M: Mantissa>(&self, is_positive: bool, rounded: Rounded<M>) -> M {
+        let field_bits = self.field_bits() as u64;
+        let sign_bit = self.exponent_bits as u64 + field_bits;
+        let sign = M::from_u128(!is_positive as u128).shl(sign_bit);
+        let (mantissa, quantum) = match rounded {
+            Rounded::Finite { mantissa, quantum } => (mantissa, quantum),
+            Rounded::Infinite => {
+                let exponent_field = M::from_u128((1 << self.exponent_bits) - 1).shl(field_bits);
+                let integer_bit =
+                    M::from_u128(self.explicit_integer_bit as u128).shl(self.mantissa_bits as u64);
+                return sign.add(&exponent_field).add(&integer_bit);
+            }
+        };
+        if mantissa.bit_length() == 0 && !self.has_signed_zero {
+            return M::from_u128(0);
+        }
+
+        // Subnormal numbers don't have a leading one and use a biased exponent
+        // of zero, and so does zero.
+        let leading_one = M::from_u128(1).shl(self.mantissa_bits as u64);
+        let (biased_exponent, mantissa_field) = if mantissa < leading_one {
+            (0, mantissa)
+        } else {
+            let biased_exponent = quantum + self.mantissa_bits as i64 + self.exponent_bias;
+            if self.explicit_integer_bit {
+                (biased_exponent, mantissa)
+            } else {
+                (biased_exponent, mantissa.sub(&leading_one))
+            }
+        };
+        let exponent_field = M::from_u128(biased_exponent as u128).shl(field_bits);
+        sign.add(&exponent_field).add(&mantissa_field)
+    }
+
+    /// Negate the bits of a number.
+    pub(crate) fn negate(&self, bits: u128) -> u128 {
+        let sign = 1 << (self.exponent_bits + self.field_bits());
+        // Without a negative zero, zero stays the same and the bits of negative
+        // zero are a NaN, which also stays the same.
+        if !self.has_signed_zero && bits & !sign == 0 {
+            bits
+        } else {
+            bits ^ sign
+        }
+    }
+
+    /// Convert a literal to bits. This only works for formats that fit in 128
+    /// bits.
+    pub(crate) fn convert_bits(
+        &self,
+        literal: &FloatLiteral,
+        mode: RoundingMode,
+    ) -> Conversion<u128> {
+        self.round_u128(literal, mode)
+            .map(|rounded| self.encode(literal.is_positive, rounded))
+    }
+
+    /// Convert a literal to bits, saturating to the largest finite number
+    /// instead of overflowing to infinity. This only works for formats that
+    /// fit in 128 bits.
+    pub(crate) fn convert_bits_saturating(
+        &self,
+        literal: &FloatLiteral,
+        mode: RoundingMode,
+    ) -> Conversion<u128> {
+        let mut conversion = self.round_u128(literal, mode);
+        if let Rounded::Infinite = conversion.value {
+            conversion.value = Rounded::Finite {
+                mantissa: self.max_mantissa(),
+                quantum: self.max_exponent() - self.mantissa_bits as i64,
+            };
+            conversion.direction = RoundingDirection::Down;
+        }
+        conversion.map(|rounded| self.encode(literal.is_positive, rounded))
+    }
+
+    /// Calculate the rounding error of converting a literal. This only works
+    /// for formats that fit in 128 bits.
+    pub(crate) fn rounding_error(
+        &self,
+        literal: &FloatLiteral,
+        mode: RoundingMode,
+    ) -> Option<RoundingError> {
+        match self.round_u128(literal, mode).value {
+            Rounded::Finite { mantissa, quantum } => {
+                Some(RoundingError::new(literal, mantissa, quantum))
+            }
+            Rounded::Infinite => None,
+        }
+    }
+
+    /// Round a literal to nearest, ties to even, and find the interval of
+    /// magnitudes that round to the same result. Returns `None` if the literal
+    /// rounds to infinity. This only works for formats that fit in 128 bits.
+    pub(crate) fn rounding_interval(&self, literal: &FloatLiteral) -> Option<RoundingInterval> {
+        let (mantissa, quantum) = match self.round_u128(literal, RoundingMode::NearestEven).value {
+            Rounded::Finite { mantissa, quantum } => (mantissa, quantum),
+            Rounded::Infinite => return None,
+        };
+        let dyadic = |mantissa, exponent| Dyadic {
+            is_positive: true,
+            mantissa: BigUint::from_u128(mantissa),
+            exponent,
+        };
+
+        // The ends are halfway to the neighbors, and round to the result if
+        // its mantissa is even. Below a power of two, the neighbor is closer,
+        // unless it is subnormal. Without subnormals, the neighbors of the
+        // smallest normal number and zero are each other, and the tie between
+        // them rounds to zero.
+        let is_even = mantissa & 1 == 0;
+        let min_quantum = self.min_exponent() - self.mantissa_bits as i64;
+        let flushes = !self.has_subnormals && quantum == min_quantum;
+        let smallest_normal = 1 << self.mantissa_bits;
+        let (low, low_inclusive) = if mantissa == 0 {
+            (dyadic(0, quantum), true)
+        } else if flushes && mantissa == smallest_normal {
+            (dyadic(1, self.min_exponent() - 1), false)
+        } else if mantissa == smallest_normal && quantum > min_quantum {
+            (dyadic(4 * mantissa - 1, quantum - 2), is_even)
+        } else {
+            (dyadic(2 * mantissa - 1, quantum - 1), is_even)
+        };
+        let max_quantum = self.max_exponent() - self.mantissa_bits as i64;
+        let is_max = quantum == max_quantum && mantissa == self.max_mantissa();
+        let high = if is_max && !self.has_infinity {
+            None
+        } else if flushes && mantissa == 0 {
+            Some(dyadic(1, self.min_exponent() - 1))
+        } else {
+            Some(dyadic(2 * mantissa + 1, quantum - 1))
+        };
+        let value = Dyadic {
+            is_positive: literal.is_positive || (mantissa == 0 && !self.has_signed_zero),
+            ..dyadic(mantissa, quantum)
+        };
+        Some(RoundingInterval {
+            low,
+            value,
+            high,
+            low_inclusive,
+            high_inclusive: is_even,
+        })
+    }
+}
@@ -0,0 +1,528 @@
+//! Exact arithmetic on literals.
+
+#[cfg(not(feature = "std"))]
+use alloc::{vec, vec::Vec};
+
+use crate::rounding::{RoundingDirection, RoundingMode};
+use crate::FloatLiteral;
+use core::cmp::Ordering;
+
+/// Trait for the limbs of the integers in [`ExactValue`](struct.ExactValue.html)
+/// and [`ExactFraction`](struct.ExactFraction.html). This is implemented for
+/// `u32` and `u64`, and is sealed, so it can't be implemented for other types.
+pub trait Limb: private::Sealed + Sized {
+    /// Combine `u32` limbs, least significant first, into one limb. There are
+    /// never more of them than fit in the limb.
+    fn from_u32_limbs(limbs: &[u32]) -> Self;
+}
+
+mod private {
+    /// Keeps `Limb` from being implemented outside the crate, because
+    /// `BigUint::to_limbs` assumes a limb is a whole number of `u32`s.
+    pub trait Sealed {}
+
+    impl Sealed for u32 {}
+    impl Sealed for u64 {}
+}
+
+impl Limb for u32 {
+    fn from_u32_limbs(limbs: &[u32]) -> u32 {
+        limbs[0]
+    }
+}
+
+impl Limb for u64 {
+    fn from_u32_limbs(limbs: &[u32]) -> u64 {
+        limbs
+            .iter()
+            .rev()
+            .fold(0, |limb, &small| (limb << 32) | small as u64)
+    }
+}
+
+/// The exact value of a literal, as `±mantissa * 2^exponent`.
+///
+/// The mantissa is stored as limbs, least significant first, without any
+/// leading zero limbs. It is odd, unless the value is zero, where there are no
+/// limbs and the exponent is zero.
+///
+/// ```rust
+/// use hexponent::{ExactValue, FloatLiteral};
+/// let literal: FloatLiteral = "-0x123456789.a".parse().unwrap();
+/// let exact = literal.to_exact::<u32>();
+/// assert_eq!(exact, ExactValue {
+///     is_positive: false,
+///     mantissa: vec![0x1a2b_3c4d, 0x9],
+///     exponent: -3,
+/// });
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ExactValue<L> {
+    /// Whether the value is positive. This is false for negative zero.
+    pub is_positive: bool,
+    /// The limbs of the mantissa, least significant first.
+    pub mantissa: Vec<L>,
+    /// The binary exponent.
+    pub exponent: i64,
+}
+
+/// The exact value of a literal, as `±numerator / denominator` in lowest
+/// terms.
+///
+/// The denominator is always a power of two, and is one when the value is an
+/// integer. The integers are stored as limbs, least significant first, without
+/// any leading zero limbs, so zero has no limbs. Literals with very large or
+/// very small exponents have very large numerators or denominators.
+///
+/// ```rust
+/// use hexponent::{ExactFraction, FloatLiteral};
+/// let literal: FloatLiteral = "0x.c".parse().unwrap();
+/// let fraction = literal.to_fraction::<u64>();
+/// assert_eq!(fraction, ExactFraction {
+///     is_positive: true,
+///     numerator: vec![3],
+///     denominator: vec![4],
+/// });
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ExactFraction<L> {
+    /// Whether the value is positive. This is false for negative zero.
+    pub is_positive: bool,
+    /// The limbs of the numerator, least significant first.
+    pub numerator: Vec<L>,
+    /// The limbs of the denominator, least significant first.
+    pub denominator: Vec<L>,
+}
+
+/// An arbitrary precision unsigned integer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct BigUint {
+    // Little endian, without any trailing zero limbs.
+    limbs: Vec<u32>,
+}
+
+impl BigUint {
+    pub(crate) fn zero() -> BigUint {
+        BigUint { limbs: Vec::new() }
+    }
+
+    pub(crate) fn from_u128(mut value: u128) -> BigUint {
+        let mut limbs = Vec::new();
+        while value != 0 {
+            limbs.push(value as u32);
+            value >>= 32;
+        }
+        BigUint { limbs }
+    }
+
+    /// Build an integer from hexadecimal digit values, most significant first.
+    pub(crate) fn from_hex_digits(digits: &[u8]) -> BigUint {
+        let mut limbs = Vec::with_capacity(digits.len() / 8 + 1);
+        for chunk in digits.rchunks(8) {
+            limbs.push(chunk.iter().fold(0, |acc, &d| (acc << 4) | d as u32));
+        }
+        let mut result = BigUint { limbs };
+        result.trim();
+        result
+    }
+
+    /// Get the hexadecimal digit values, most significant first. Zero has no
+    /// digits.
+    pub(crate) fn to_hex_digits(&self) -> Vec<u8> {
+        let mut digits = Vec::with_capacity(self.limbs.len() * 8);
+        for &limb in self.limbs.iter().rev() {
+            for shift in (0..8).rev() {
+                digits.push((limb >> (shift * 4)) as u8 & 0xf);
+            }
+        }
+        let first_digit = digits.iter().position(|&d| d != 0).unwrap_or(digits.len());
+        digits.drain(..first_digit);
+        digits
+    }
+
+    /// Get the limbs, least significant first, without any leading zero limbs.
+    pub(crate) fn to_limbs<L: Limb>(&self) -> Vec<L> {
+        let chunk_size = core::mem::size_of::<L>() / 4;
+        self.limbs
+            .chunks(chunk_size)
+            .map(L::from_u32_limbs)
+            .collect()
+    }
+
+    /// Get the decimal digit values, most significant first. Zero has no
+    /// digits.
+    pub(crate) fn to_decimal_digits(&self) -> Vec<u8> {
+        // Split off 9 digits at a time, least significant first.
+        let mut chunks = Vec::new();
+        let mut remaining = self.clone();
+        while !remaining.is_zero() {
+            let (quotient, chunk) = remaining.div_rem_small(1_000_000_000);
+            chunks.push(chunk);
+            remaining = quotient;
+        }
+        let mut digits = Vec::with_capacity(chunks.len() * 9);
+        for chunk in chunks.iter().rev() {
+            for power in (0..9).rev() {
+                digits.push((chunk / 10u32.pow(power) % 10) as u8);
+            }
+        }
+        let first_digit = digits.iter().position(|&d| d != 0).unwrap_or(digits.len());
+        digits.drain(..first_digit);
+        digits
+    }
+
+    pub(crate) fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    /// The number of bits, not counting leading zeros.
+    pub(crate) fn bit_length(&self) -> u64 {
+        match self.limbs.last() {
+            Some(&top) => self.limbs.len() as u64 * 32 - top.leading_zeros() as u64,
+            None => 0,
+        }
+    }
+
+    /// The number of trailing zero bits. Zero has no trailing zeros.
+    pub(crate) fn trailing_zeros(&self) -> u64 {
+        match self.limbs.iter().position(|&limb| limb != 0) {
+            Some(index) => index as u64 * 32 + self.limbs[index].trailing_zeros() as u64,
+            None => 0,
+        }
+    }
+
+    pub(crate) fn bit(&self, index: u64) -> bool {
+        match self.limbs.get((index / 32) as usize) {
+            Some(&limb) => (limb >> (index % 32)) & 1 == 1,
+            None => false,
+        }
+    }
+
+    /// Return whether any of the bits below `index` are set.
+    pub(crate) fn any_below(&self, index: u64) -> bool {
+        let limb_index = (index / 32) as usize;
+        let partial = match self.limbs.get(limb_index) {
+            Some(&limb) => limb & ((1 << (index % 32)) - 1) != 0,
+            None => false,
+        };
+        partial || self.limbs.iter().take(limb_index).any(|&limb| limb != 0)
+    }
+
+    /// Get the lowest `length` bytes, least significant first.
+    pub(crate) fn to_le_bytes(&self, length: usize) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.limbs.len() * 4);
+        for limb in &self.limbs {
+            bytes.extend_from_slice(&limb.to_le_bytes());
+        }
+        bytes.resize(length, 0);
+        bytes
+    }
+
+    pub(crate) fn shl(&self, bits: u64) -> BigUint {
+        if self.is_zero() {
+            return BigUint::zero();
+        }
+        let limb_shift = (bits / 32) as usize;
+        let bit_shift = (bits % 32) as u32;
+        let mut limbs = vec![0; limb_shift];
+        let mut carry = 0;
+        for &limb in &self.limbs {
+            let wide = ((limb as u64) << bit_shift) | carry;
+            limbs.push(wide as u32);
+            carry = wide >> 32;
+        }
+        limbs.push(carry as u32);
+        let mut result = BigUint { limbs };
+        result.trim();
+        result
+    }
+
+    pub(crate) fn shr(&self, bits: u64) -> BigUint {
+        let limb_shift = (bits / 32) as usize;
+        let bit_shift = bits % 32;
+        if limb_shift >= self.limbs.len() {
+            return BigUint::zero();
+        }
+        let mut limbs = Vec::with_capacity(self.limbs.len() - limb_shift);
+        for index in limb_shift..self.limbs.len() {
+            let low = self.limbs[index] as u64;
+            let high = self.limbs.get(index + 1).copied().unwrap_or(0) as u64;
+            limbs.push((((high << 32) | low) >> bit_shift) as u32);
+        }
+        let mut result = BigUint { limbs };
+        result.trim();
+        result
+    }
+
+    pub(crate) fn add(&self, other: &BigUint) -> BigUint {
+        let length = self.limbs.len().max(other.limbs.len());
+        let mut limbs = Vec::with_capacity(length + 1);
+        let mut carry = 0;
+        for index in 0..length {
+            let a = self.limbs.get(index).copied().unwrap_or(0) as u64;
+            let b = other.limbs.get(index).copied().unwrap_or(0) as u64;
+            let sum = a + b + carry;
+            limbs.push(sum as u32);
+            carry = sum >> 32;
+        }
+        limbs.push(carry as u32);
+        let mut result = BigUint { limbs };
+        result.trim();
+        result
+    }
+
+    pub(crate) fn mul_small(&self, factor: u32) -> BigUint {
+        let mut limbs = Vec::with_capacity(self.limbs.len() + 1);
+        let mut carry = 0;
+        for &limb in &self.limbs {
+            let product = limb as u64 * factor as u64 + carry;
+            limbs.push(product as u32);
+            carry = product >> 32;
+        }
+        limbs.push(carry as u32);
+        let mut result = BigUint { limbs };
+        result.trim();
+        result
+    }
+
+    /// Divide by `divisor`, returning the quotient and the remainder.
+    pub(crate) fn div_rem_small(&self, divisor: u32) -> (BigUint, u32) {
+        let mut limbs = vec![0; self.limbs.len()];
+        let mut remainder = 0;
+        for index in (0..self.limbs.len()).rev() {
+            let dividend = (remainder << 32) | self.limbs[index] as u64;
+            limbs[index] = (dividend / divisor as u64) as u32;
+            remainder = dividend % divisor as u64;
+        }
+        let mut result = BigUint { limbs };
+        result.trim();
+        (result, remainder as u32)
+    }
+
+    pub(crate) fn mul(&self, other: &BigUint) -> BigUint {
+        let mut limbs = vec![0; self.limbs.len() + other.limbs.len()];
+        for (i, &a) in self.limbs.iter().enumerate() {
+            let mut carry = 0;
+            for (j, &b) in other.limbs.iter().enumerate() {
+                let product = a as u64 * b as u64 + limbs[i + j] as u64 + carry;
+                limbs[i + j] = product as u32;
+                carry = product >> 32;
+            }
+            limbs[i + other.limbs.len()] = carry as u32;
+        }
+        let mut result = BigUint { limbs };
+        result.trim();
+        result
+    }
+
+    /// Divide by `divisor`, which must not be zero, returning the quotient and
+    /// the remainder. This takes one step for each bit of the quotient, so it
+    /// is only meant for small quotients.
+    pub(crate) fn div_rem(&self, divisor: &BigUint) -> (BigUint, BigUint) {
+        debug_assert!(!divisor.is_zero());
+        let mut quotient = BigUint::zero();
+        let mut remainder = self.clone();
+        let quotient_bits = self.bit_length().saturating_sub(divisor.bit_length());
+        for bit in (0..=quotient_bits).rev() {
+            let shifted = divisor.shl(bit);
+            if remainder >= shifted {
+                remainder = remainder.sub(&shifted);
+                quotient = quotient.add(&BigUint::from_u128(1).shl(bit));
+            }
+        }
+        (quotient, remainder)
+    }
+
+    /// Subtract `other`, which must not be larger than `self`.
+    pub(crate) fn sub(&self, other: &BigUint) -> BigUint {
+        debug_assert!(*self >= *other);
+        let mut limbs = Vec::with_capacity(self.limbs.len());
+        let mut borrow = 0;
+        for index in 0..self.limbs.len() {
+            let a = self.limbs[index] as i64;
+            let b = other.limbs.get(index).copied().unwrap_or(0) as i64;
+            let mut difference = a - b - borrow;
+            borrow = 0;
+            if difference < 0 {
+                difference += 1 << 32;
+                borrow = 1;
+            }
+            limbs.push(difference as u32);
+        }
+        let mut result = BigUint { limbs };
+        result.trim();
+        result
+    }
+
+    fn trim(&mut self) {
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &BigUint) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &BigUint) -> Ordering {
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
+
+/// An exact signed value of `mantissa * 2^exponent`.
+#[derive(Debug, Clone)]
+pub(crate) struct Dyadic {
+    pub(crate) is_positive: bool,
+    pub(crate) mantissa: BigUint,
+    pub(crate) exponent: i64,
+}
+
+impl Dyadic {
+    pub(crate) fn from_literal(literal: &FloatLiteral) -> Dyadic {
+        // 4 bits for each hexadecimal digit after the last one
+        let exponent = literal.exponent as i64
+            + 4 * (literal.decimal_offset as i64 - literal.digits.len() as i64);
+        Dyadic {
+            is_positive: literal.is_positive,
+            mantissa: BigUint::from_hex_digits(&literal.digits),
+            exponent,
+        }
+    }
+
+    /// Get the same value with an odd mantissa, or a zero exponent for zero.
+    pub(crate) fn normalize(&self) -> Dyadic {
+        if self.mantissa.is_zero() {
+            return Dyadic {
+                is_positive: self.is_positive,
+                mantissa: BigUint::zero(),
+                exponent: 0,
+            };
+        }
+        let trailing_zeros = self.mantissa.trailing_zeros();
+        Dyadic {
+            is_positive: self.is_positive,
+            mantissa: self.mantissa.shr(trailing_zeros),
+            exponent: self.exponent + trailing_zeros as i64,
+        }
+    }
+
+    pub(crate) fn to_literal(&self) -> FloatLiteral {
+        // Keep as much of the exponent as possible in the binary exponent.
+        // Anything that doesn't fit goes in the digit offset instead.
+        let exponent = self
+            .exponent
+            .max(core::i32::MIN as i64)
+            .min(core::i32::MAX as i64);
+        let remaining = self.exponent - exponent;
+        let digit_offset = remaining >> 2;
+        let mantissa = self.mantissa.shl((remaining & 3) as u64);
+
+        let mut digits = mantissa.to_hex_digits();
+        let decimal_offset = digits.len() as i64 + digit_offset;
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+        if digits.is_empty() {
+            return FloatLiteral {
+                is_positive: self.is_positive,
+                digits,
+                decimal_offset: 0,
+                exponent: 0,
+            };
+        }
+        FloatLiteral {
+            is_positive: self.is_positive,
+            digits,
+            decimal_offset: decimal_offset as i32,
+            exponent: exponent as i32,
+        }
+    }
+
+    /// Round the magnitude to an integer multiple of `2^quantum`. Returns the
+    /// multiple and the direction it was rounded in.
+    pub(crate) fn round_to_quantum(
+        &self,
+        quantum: i64,
+        mode: RoundingMode,
+    ) -> (BigUint, RoundingDirection) {
+        if quantum <= self.exponent {
+            let multiple = self.mantissa.shl((self.exponent - quantum) as u64);
+            return (multiple, RoundingDirection::Exact);
+        }
+
+        let shift = (quantum - self.exponent) as u64;
+        let multiple = self.mantissa.shr(shift);
+        let round = self.mantissa.bit(shift - 1);
+        let sticky = self.mantissa.any_below(shift - 1);
+        if !round && !sticky {
+            (multiple, RoundingDirection::Exact)
+        } else if mode.rounds_up(self.is_positive, multiple.bit(0), round, sticky) {
+            (multiple.add(&BigUint::from_u128(1)), RoundingDirection::Up)
+        } else {
+            (multiple, RoundingDirection::Down)
+        }
+    }
+
+    /// Return `self * other`.
+    pub(crate) fn mul(&self, other: &Dyadic) -> Dyadic {
+        Dyadic {
+            is_positive: self.is_positive == other.is_positive,
+            mantissa: self.mantissa.mul(&other.mantissa),
+            exponent: self.exponent + other.exponent,
+        }
+    }
+
+    /// Return `self - other`.
+    pub(crate) fn sub(&self, other: &Dyadic) -> Dyadic {
+        let negated = Dyadic {
+            is_positive: !other.is_positive,
+            mantissa: other.mantissa.clone(),
+            exponent: other.exponent,
+        };
+        self.add(&negated)
+    }
+
+    /// Return `self + other`.
+    pub(crate) fn add(&self, other: &Dyadic) -> Dyadic {
+        // Zeros don't need to be aligned, which matters when the exponents are
+        // far apart. Like IEEE 754, the sum of zeros is only negative if both
+        // are negative.
+        if self.mantissa.is_zero() && other.mantissa.is_zero() {
+            return Dyadic {
+                is_positive: self.is_positive || other.is_positive,
+                ..self.clone()
+            };
+        }
+        if other.mantissa.is_zero() {
+            return self.clone();
+        }
+        if self.mantissa.is_zero() {
+            return other.clone();
+        }
+
+        let exponent = self.exponent.min(other.exponent);
+        let a = self.mantissa.shl((self.exponent - exponent) as u64);
+        let b = other.mantissa.shl((other.exponent - exponent) as u64);
+        let (is_positive, mantissa) = if self.is_positive == other.is_positive {
+            (self.is_positive, a.add(&b))
+        } else if a > b {
+            (self.is_positive, a.sub(&b))
+        } else if a == b {
+            (true, BigUint::zero())
+        } else {
+            (other.is_positive, b.sub(&a))
+        };
+        Dyadic {
+            is_positive,
+            mantissa,
+            exponent,
+        }
+    }
+}
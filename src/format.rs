@@ -0,0 +1,291 @@
+//! Formatting floats as hexadecimal literals.
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+
+use crate::exact::{BigUint, Dyadic};
+use core::fmt::Write;
+
+fn push_repeated(output: &mut String, c: char, count: usize) {
+    for _ in 0..count {
+        output.push(c);
+    }
+}
+
+/// Options for formatting floats in hexadecimal, like the `%a` conversion of
+/// C's `printf`. The default options match a plain `%a`.
+///
+/// ```rust
+/// use hexponent::HexFormatOptions;
+/// let plain = HexFormatOptions::default();
+/// assert_eq!(plain.format_f64(-0.1), "-0x1.999999999999ap-4");
+///
+/// // Like `%+#012.0A`.
+/// let options = HexFormatOptions {
+///     precision: Some(0),
+///     uppercase: true,
+///     plus_sign: true,
+///     alternate: true,
+///     width: 12,
+///     zero_pad: true,
+///     ..HexFormatOptions::default()
+/// };
+/// assert_eq!(options.format_f64(1.5), "+0X00002.P+0");
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HexFormatOptions {
+    /// The number of digits after the point. The value is rounded to nearest,
+    /// ties to even, if there are fewer digits than needed to be exact. If this
+    /// is `None`, there are exactly as many as needed.
+    pub precision: Option<usize>,
+    /// Whether to use uppercase letters, like `%A`.
+    pub uppercase: bool,
+    /// Whether positive numbers get a `+` sign, like the `+` flag.
+    pub plus_sign: bool,
+    /// Whether positive numbers get a space instead of a sign, like the space
+    /// flag. This is ignored if `plus_sign` is set.
+    pub space_sign: bool,
+    /// Whether to write the point even if there are no digits after it, like
+    /// the `#` flag.
+    pub alternate: bool,
+    /// The minimum width of the output, which is padded with spaces on the
+    /// left.
+    pub width: usize,
+    /// Whether to pad with zeros after the `0x` prefix instead, like the `0`
+    /// flag. This is ignored for infinities and NaN.
+    pub zero_pad: bool,
+    /// Whether to pad with spaces on the right instead, like the `-` flag.
+    /// This overrides `zero_pad`.
+    pub left_align: bool,
+}
+
+impl HexFormatOptions {
+    /// Format an `f32`. Like `printf`, this formats the number as the `f64`
+    /// with the same value, so subnormal numbers are normalized.
+    pub fn format_f32(&self, value: f32) -> String {
+        self.format_f64(f64::from(value))
+    }
+
+    /// Format an `f64`. This matches the output of glibc's `printf`. Normal
+    /// numbers have a leading digit of 1, subnormal numbers have a leading
+    /// digit of 0 and an exponent of -1022, and zero has an exponent of 0.
+    /// Rounding can carry into the leading digit, like `0x2p+0`.
+    pub fn format_f64(&self, value: f64) -> String {
+        let bits = value.to_bits();
+        let is_positive = bits >> 63 == 0;
+        let biased_exponent = ((bits >> 52) & 0x7ff) as i64;
+        let fraction = bits & ((1 << 52) - 1);
+
+        let sign = if !is_positive {
+            "-"
+        } else if self.plus_sign {
+            "+"
+        } else if self.space_sign {
+            " "
+        } else {
+            ""
+        };
+
+        if biased_exponent == 0x7ff {
+            let text = match (fraction == 0, self.uppercase) {
+                (true, false) => "inf",
+                (true, true) => "INF",
+                (false, false) => "nan",
+                (false, true) => "NAN",
+            };
+            return self.pad(sign, "", text, false);
+        }
+
+        let (leading, exponent) = if biased_exponent != 0 {
+            (1, biased_exponent - 1023)
+        } else if fraction != 0 {
+            (0, -1022)
+        } else {
+            (0, 0)
+        };
+
+        // The 52 fraction bits are exactly 13 hexadecimal digits.
+        let (digit_count, leading, fraction) = match self.precision {
+            None => {
+                let trailing_digits = if fraction == 0 {
+                    13
+                } else {
+                    fraction.trailing_zeros() / 4
+                };
+                (
+                    13 - trailing_digits as usize,
+                    leading,
+                    fraction >> (4 * trailing_digits),
+                )
+            }
+            Some(precision) if precision >= 13 => (precision, leading, fraction),
+            Some(precision) => {
+                // Round the digits, including the leading one, to nearest,
+                // ties to even. This can carry into the leading digit.
+                let shift = 4 * (13 - precision as u32);
+                let all_digits = (leading << 52) | fraction;
+                let mut kept = all_digits >> shift;
+                let dropped = all_digits & ((1 << shift) - 1);
+                let half = 1 << (shift - 1);
+                if dropped > half || (dropped == half && kept & 1 == 1) {
+                    kept += 1;
+                }
+                let digits_mask = (1 << (4 * precision)) - 1;
+                (precision, kept >> (4 * precision), kept & digits_mask)
+            }
+        };
+
+        let mut body = String::new();
+        write!(body, "{}", leading).unwrap();
+        if digit_count > 0 || self.alternate {
+            body.push('.');
+        }
+        // Digits past the 13th are always zero.
+        let exact_digits = digit_count.min(13);
+        if exact_digits > 0 {
+            if self.uppercase {
+                write!(body, "{:01$X}", fraction, exact_digits).unwrap();
+            } else {
+                write!(body, "{:01$x}", fraction, exact_digits).unwrap();
+            }
+        }
+        push_repeated(&mut body, '0', digit_count - exact_digits);
+        let (exponent_sign, exponent) = if exponent < 0 {
+            ('-', -exponent)
+        } else {
+            ('+', exponent)
+        };
+        let p = if self.uppercase { 'P' } else { 'p' };
+        write!(body, "{}{}{}", p, exponent_sign, exponent).unwrap();
+
+        let prefix = if self.uppercase { "0X" } else { "0x" };
+        self.pad(sign, prefix, &body, self.zero_pad)
+    }
+
+    /// Join the parts of a formatted number and pad them to the width. Zeros go
+    /// between the prefix and the body.
+    fn pad(&self, sign: &str, prefix: &str, body: &str, zero_pad: bool) -> String {
+        let length = sign.len() + prefix.len() + body.len();
+        let padding = self.width.saturating_sub(length);
+        let mut output = String::with_capacity(length + padding);
+        if self.left_align {
+            output.push_str(sign);
+            output.push_str(prefix);
+            output.push_str(body);
+            push_repeated(&mut output, ' ', padding);
+        } else if zero_pad {
+            output.push_str(sign);
+            output.push_str(prefix);
+            push_repeated(&mut output, '0', padding);
+            output.push_str(body);
+        } else {
+            push_repeated(&mut output, ' ', padding);
+            output.push_str(sign);
+            output.push_str(prefix);
+            output.push_str(body);
+        }
+        output
+    }
+}
+
+/// How subnormal numbers are written by `format_f32_exact` and
+/// `format_f64_exact`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SubnormalStyle {
+    /// With a leading `0` and the exponent of the smallest normal numbers, like
+    /// `0x0.0000000000001p-1022`.
+    Denormalized,
+    /// With a leading `1` like normal numbers, like `0x1p-1074`.
+    Normalized,
+}
+
+/// Format the bits of a binary float as the shortest exact hexadecimal
+/// literal.
+fn format_exact(
+    bits: u64,
+    exponent_bits: u32,
+    mantissa_bits: u32,
+    style: SubnormalStyle,
+) -> String {
+    let is_positive = bits >> (exponent_bits + mantissa_bits) == 0;
+    let max_biased_exponent = (1 << exponent_bits) - 1;
+    let biased_exponent = (bits >> mantissa_bits) & max_biased_exponent;
+    let fraction = bits & ((1 << mantissa_bits) - 1);
+    let min_exponent = 2 - (1 << (exponent_bits - 1));
+    let sign = if is_positive { "" } else { "-" };
+
+    if biased_exponent == max_biased_exponent {
+        let text = if fraction == 0 { "inf" } else { "nan" };
+        return format!("{}{}", sign, text);
+    }
+
+    if biased_exponent == 0 && fraction != 0 && style == SubnormalStyle::Denormalized {
+        // Pad the fraction to a whole number of digits, then trim the zeros.
+        let padding = (4 - mantissa_bits % 4) % 4;
+        let digit_count = (mantissa_bits + padding) / 4;
+        let trailing_digits = (fraction << padding).trailing_zeros() / 4;
+        let digits = (fraction << padding) >> (4 * trailing_digits);
+        return format!(
+            "{}0x0.{:03$x}p{:+}",
+            sign,
+            digits,
+            min_exponent,
+            (digit_count - trailing_digits) as usize
+        );
+    }
+
+    // Normal numbers, and normalized subnormal numbers, are written the same
+    // way as a normalized `FloatLiteral`.
+    let (mantissa, exponent) = if biased_exponent == 0 {
+        (fraction, min_exponent)
+    } else {
+        (
+            fraction | 1 << mantissa_bits,
+            biased_exponent as i64 - 1 + min_exponent,
+        )
+    };
+    let exact = Dyadic {
+        is_positive,
+        mantissa: BigUint::from_u128(mantissa as u128),
+        exponent: exponent - mantissa_bits as i64,
+    };
+    exact.to_literal().to_string()
+}
+
+/// Format an `f32` as the shortest hexadecimal literal with exactly its value,
+/// like `0x1.8p+3`. Trailing zeros are trimmed, and there is a leading `1`
+/// unless the number is zero or a subnormal number written in the
+/// `Denormalized` style. Finite numbers can be parsed back with
+/// `FloatLiteral`'s `FromStr` implementation, while infinities and NaN are
+/// written as `inf` and `nan`, with a sign if they are negative.
+///
+/// ```rust
+/// use hexponent::{format_f32_exact, SubnormalStyle};
+/// assert_eq!(format_f32_exact(-0.1, SubnormalStyle::Normalized), "-0x1.99999ap-4");
+/// let min_subnormal = f32::from_bits(1);
+/// assert_eq!(format_f32_exact(min_subnormal, SubnormalStyle::Normalized), "0x1p-149");
+/// assert_eq!(format_f32_exact(min_subnormal, SubnormalStyle::Denormalized), "0x0.000002p-126");
+/// ```
+pub fn format_f32_exact(value: f32, subnormals: SubnormalStyle) -> String {
+    format_exact(value.to_bits() as u64, 8, 23, subnormals)
+}
+
+/// Format an `f64` as the shortest hexadecimal literal with exactly its value.
+/// See `format_f32_exact` for the details.
+///
+/// ```rust
+/// use hexponent::{format_f64_exact, SubnormalStyle};
+/// assert_eq!(format_f64_exact(1.0, SubnormalStyle::Normalized), "0x1p+0");
+/// let min_subnormal = f64::from_bits(1);
+/// assert_eq!(format_f64_exact(min_subnormal, SubnormalStyle::Normalized), "0x1p-1074");
+/// assert_eq!(
+///     format_f64_exact(min_subnormal, SubnormalStyle::Denormalized),
+///     "0x0.0000000000001p-1022"
+/// );
+/// ```
+pub fn format_f64_exact(value: f64, subnormals: SubnormalStyle) -> String {
+    format_exact(value.to_bits(), 11, 52, subnormals)
+}
@@ -1,118 +1,455 @@
-use crate::{ConversionResult, FloatLiteral};
+use crate::rounding::RoundingMode;
+use crate::{Conversion, ConversionResult, FloatLiteral, FormatDescriptor, RoundingError};
 use core::ops;
 
-macro_rules! impl_fpformat {
-    ($fp_type:ty, $bits_type:ty, $exponent_bits: literal, $mantissa_bits: literal, $from_bits: expr, $infinity: expr, $max_exp: expr, $min_exp: expr) => {
-        impl FPFormat for $fp_type {
-            fn from_literal(literal: FloatLiteral) -> ConversionResult<$fp_type> {
-                const EXPONENT_BITS: u32 = $exponent_bits;
-                const MANTISSA_BITS: u32 = $mantissa_bits;
-
-                const TOTAL_BITS: u32 = 1 + EXPONENT_BITS + MANTISSA_BITS;
-
-                // The spec always gives an exponent bias that follows this formula.
-                const EXPONENT_BIAS: u32 = (1 << (EXPONENT_BITS - 1)) - 1;
-
-                // 4 bits for each hexadecimal offset
-                let mut exponent_offset: i32 = literal.decimal_offset * 4;
-
-                // If there were all 0s, return 0 or -0.
-                if literal.digits.is_empty() {
-                    if literal.is_positive {
-                        return ConversionResult::Precise(0.0);
-                    } else {
-                        return ConversionResult::Precise(-0.0);
-                    };
-                }
-
-                // This code is a work of art.
-                let mut was_truncated = false;
-                let mut mantissa_result: $bits_type = 0;
-                for (index, digit) in literal.digits.iter().enumerate() {
-                    if index as u32 * 4 > MANTISSA_BITS {
-                        was_truncated = true;
-                        break;
-                    }
-                    let mut digit_value = *digit as $bits_type;
-                    digit_value <<= TOTAL_BITS - (index as u32 + 1) * 4;
-                    mantissa_result |= digit_value;
-                }
-                let leading_zeros = mantissa_result.leading_zeros();
-                exponent_offset -= leading_zeros as i32 + 1;
-                mantissa_result <<= leading_zeros + 1;
-                mantissa_result >>= TOTAL_BITS - MANTISSA_BITS;
-
-                let final_exponent = exponent_offset + literal.exponent;
-
-                // Check for underflows
-                if final_exponent < $min_exp - 1 {
-                    // TODO: Implement subnormal numbers.
-                    if literal.is_positive {
-                        return ConversionResult::Imprecise(0.0);
-                    } else {
-                        return ConversionResult::Imprecise(-0.0);
-                    };
-                }
-
-                // Check for overflows
-                if final_exponent > $max_exp - 1 {
-                    if literal.is_positive {
-                        return ConversionResult::Imprecise($infinity);
-                    } else {
-                        return ConversionResult::Imprecise(-$infinity);
-                    };
-                }
-
-                let exponent_result: $bits_type =
-                    ((final_exponent + EXPONENT_BIAS as i32) as $bits_type) << MANTISSA_BITS;
-
-                let sign_result: $bits_type =
-                    (!literal.is_positive as $bits_type) << (MANTISSA_BITS + EXPONENT_BITS);
-
-                let float_value = $from_bits(sign_result | exponent_result | mantissa_result);
-
-                if was_truncated {
-                    ConversionResult::Imprecise(float_value)
-                } else {
-                    ConversionResult::Precise(float_value)
-                }
-
-                // // This might be a bit faster.
-                // let mut final_result = !literal.is_positive as $bits_type;
-                // final_result <<= EXPONENT_BITS;
-                // final_result |= (final_exponent + EXPONENT_BIAS as i32) as $bits_type;
-                // final_result <<= MANTISSA_BITS;
-                // final_result |= mantissa_result;
-                // ConversionResult::Precise($from_bits(final_result))
+/// The layout of a format implementing `FPFormat`.
+pub(crate) fn layout<F: FPFormat>() -> FormatDescriptor {
+    let format = F::FORMAT;
+    format.check_exponent_bits();
+    assert!(
+        format.total_bits() <= 128,
+        "an FPFormat has at most 128 bits"
+    );
+    format
+}
+
+/// Trait to describe conversion to binary floating point formats.
+///
+/// A format is described by its layout, as a
+/// [`FormatDescriptor`](struct.FormatDescriptor.html): a sign bit, then the
+/// biased exponent, then the mantissa. Implementing the trait only needs the
+/// layout and a way to create a number from its bits, and the conversions are
+/// provided.
+///
+/// The exponent must have between 1 and 32 bits, and the whole number must fit
+/// in 128 bits, so there are at most 126 mantissa bits. The conversions panic
+/// if the layout is outside these limits. Use `FormatDescriptor::convert` for
+/// wider formats.
+///
+/// # Equality
+///
+/// The formats that this crate adds are stored as their bits, and equality
+/// compares the bits. Unlike `f32` and `f64`, this means that `0.0` and `-0.0`
+/// are different, and that NaNs with the same bits are equal.
+///
+/// ```rust
+/// use hexponent::{FloatLiteral, FormatDescriptor, FPFormat};
+/// use std::ops::Neg;
+///
+/// /// TensorFloat-32, stored in the lowest 19 bits.
+/// #[derive(Clone, Copy, Debug, PartialEq)]
+/// struct Tf32(u32);
+///
+/// impl Neg for Tf32 {
+///     type Output = Tf32;
+///     fn neg(self) -> Tf32 {
+///         Tf32(self.0 ^ (1 << 18))
+///     }
+/// }
+///
+/// impl FPFormat for Tf32 {
+///     const FORMAT: FormatDescriptor = FormatDescriptor::ieee(8, 10);
+///
+///     fn from_bits(bits: u128) -> Tf32 {
+///         Tf32(bits as u32)
+///     }
+/// }
+///
+/// let literal: FloatLiteral = "0x1.8p1".parse().unwrap();
+/// assert_eq!(literal.convert::<Tf32>().inner(), Tf32(0x20200));
+/// ```
+pub trait FPFormat: ops::Neg<Output = Self> + Sized + Copy {
+    /// The layout of the format.
+    const FORMAT: FormatDescriptor;
+
+    /// Create a number from its bits, which are in the lowest bits of `bits`.
+    fn from_bits(bits: u128) -> Self;
+
+    /// Convert a literal to this format, rounding to nearest, ties to even.
+    fn from_literal(literal: FloatLiteral) -> ConversionResult<Self> {
+        Self::from_literal_rounded(literal, RoundingMode::NearestEven)
+    }
+
+    /// Convert a literal to this format with the given rounding mode.
+    fn from_literal_rounded(literal: FloatLiteral, mode: RoundingMode) -> ConversionResult<Self> {
+        Self::from_literal_with_flags(literal, mode).result()
+    }
+
+    /// Convert a literal to this format with the given rounding mode and report
+    /// the exception flags.
+    fn from_literal_with_flags(literal: FloatLiteral, mode: RoundingMode) -> Conversion<Self> {
+        layout::<Self>()
+            .convert_bits(&literal, mode)
+            .map(Self::from_bits)
+    }
+
+    /// Calculate the error of converting a literal to this format with the
+    /// given rounding mode, or `None` if the literal overflows to infinity.
+    fn rounding_error(literal: &FloatLiteral, mode: RoundingMode) -> Option<RoundingError> {
+        layout::<Self>().rounding_error(literal, mode)
+    }
+}
+
+impl FPFormat for f32 {
+    const FORMAT: FormatDescriptor = FormatDescriptor::ieee(8, 23);
+
+    fn from_bits(bits: u128) -> f32 {
+        f32::from_bits(bits as u32)
+    }
+}
+
+impl FPFormat for f64 {
+    const FORMAT: FormatDescriptor = FormatDescriptor::ieee(11, 52);
+
+    fn from_bits(bits: u128) -> f64 {
+        f64::from_bits(bits as u64)
+    }
+}
+
+impl FPFormat for F16 {
+    const FORMAT: FormatDescriptor = FormatDescriptor::ieee(5, 10);
+
+    fn from_bits(bits: u128) -> F16 {
+        F16(bits as u16)
+    }
+}
+
+impl FPFormat for BF16 {
+    const FORMAT: FormatDescriptor = FormatDescriptor::ieee(8, 7);
+
+    fn from_bits(bits: u128) -> BF16 {
+        BF16(bits as u16)
+    }
+}
+
+impl FPFormat for F80 {
+    const FORMAT: FormatDescriptor = FormatDescriptor {
+        explicit_integer_bit: true,
+        ..FormatDescriptor::ieee(15, 63)
+    };
+
+    fn from_bits(bits: u128) -> F80 {
+        F80(bits)
+    }
+}
+
+impl FPFormat for F128 {
+    const FORMAT: FormatDescriptor = FormatDescriptor::ieee(15, 112);
+
+    fn from_bits(bits: u128) -> F128 {
+        F128(bits)
+    }
+}
+
+macro_rules! fp8_type {
+    ($name:ident, $format:expr, $nan:literal, $doc:literal) => {
+        #[doc = $doc]
+        ///
+        /// [Equality](trait.FPFormat.html#equality) compares the bits.
+        #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+        pub struct $name(u8);
+
+        impl $name {
+            /// The NaN encoding.
+            pub const NAN: $name = $name($nan);
+
+            /// Create a number from its bits.
+            pub const fn from_bits(bits: u8) -> $name {
+                $name(bits)
+            }
+
+            /// Get the bits of the number.
+            pub const fn to_bits(self) -> u8 {
+                self.0
+            }
+        }
+
+        impl ops::Neg for $name {
+            type Output = $name;
+            fn neg(self) -> $name {
+                $name($format.negate(self.0 as u128) as u8)
+            }
+        }
+
+        impl FPFormat for $name {
+            const FORMAT: FormatDescriptor = $format;
+
+            fn from_bits(bits: u128) -> $name {
+                $name(bits as u8)
             }
         }
     };
 }
 
-/// Trait to describe conversion to floating point formats.
-pub trait FPFormat: ops::Neg<Output = Self> + Sized + Copy {
-    /// Convert a literal to this format. This is a hack so that we can use
-    /// a macro to implement conversions.
-    fn from_literal(literal: FloatLiteral) -> ConversionResult<Self>;
-}
-
-impl_fpformat!(
-    f32,
-    u32,
-    8,
-    23,
-    f32::from_bits,
-    core::f32::INFINITY,
-    core::f32::MAX_EXP,
-    core::f32::MIN_EXP
+/// OCP FP8 E5M2
+pub(crate) const FP8_E5M2: FormatDescriptor = FormatDescriptor::ieee(5, 2);
+/// OCP FP8 E4M3
+pub(crate) const FP8_E4M3: FormatDescriptor = FormatDescriptor {
+    has_infinity: false,
+    ..FormatDescriptor::ieee(4, 3)
+};
+/// FP8 E5M2 with finite values only and no negative zero
+const FP8_E5M2_FNUZ: FormatDescriptor = FormatDescriptor {
+    exponent_bias: 16,
+    has_infinity: false,
+    has_signed_zero: false,
+    ..FormatDescriptor::ieee(5, 2)
+};
+/// FP8 E4M3 with finite values only and no negative zero
+const FP8_E4M3_FNUZ: FormatDescriptor = FormatDescriptor {
+    exponent_bias: 8,
+    has_infinity: false,
+    has_signed_zero: false,
+    ..FormatDescriptor::ieee(4, 3)
+};
+/// OCP FP6 E3M2
+pub(crate) const FP6_E3M2: FormatDescriptor = FormatDescriptor {
+    has_infinity: false,
+    has_nan: false,
+    ..FormatDescriptor::ieee(3, 2)
+};
+/// OCP FP6 E2M3
+pub(crate) const FP6_E2M3: FormatDescriptor = FormatDescriptor {
+    has_infinity: false,
+    has_nan: false,
+    ..FormatDescriptor::ieee(2, 3)
+};
+/// The VAX formats are `0.1fraction * 2^(exponent - bias)`, which is
+/// `1.fraction * 2^(exponent - bias - 1)`. There are no subnormal numbers or
+/// infinities, and the bits of negative zero are the reserved operand.
+const fn vax_format(exponent_bits: u32, mantissa_bits: u32, bias: i64) -> FormatDescriptor {
+    FormatDescriptor {
+        exponent_bias: bias + 1,
+        has_subnormals: false,
+        has_infinity: false,
+        has_nan: false,
+        has_signed_zero: false,
+        ..FormatDescriptor::ieee(exponent_bits, mantissa_bits)
+    }
+}
+/// VAX F_floating
+pub(crate) const VAX_F: FormatDescriptor = vax_format(8, 23, 128);
+/// VAX D_floating
+pub(crate) const VAX_D: FormatDescriptor = vax_format(8, 55, 128);
+/// VAX G_floating
+pub(crate) const VAX_G: FormatDescriptor = vax_format(11, 52, 1024);
+/// OCP FP4 E2M1
+pub(crate) const FP4_E2M1: FormatDescriptor = FormatDescriptor {
+    has_infinity: false,
+    has_nan: false,
+    ..FormatDescriptor::ieee(2, 1)
+};
+
+fp8_type!(
+    F8E5M2,
+    FP8_E5M2,
+    0x7e,
+    "An OCP FP8 E5M2 number, stored as its bits. This follows the IEEE 754 rules,
+with infinities, NaNs and subnormal numbers."
+);
+fp8_type!(
+    F8E4M3,
+    FP8_E4M3,
+    0x7f,
+    "An OCP FP8 E4M3 number, stored as its bits. This has no infinities, so
+numbers that are too large saturate to ±448. The only NaNs have all of the
+exponent and mantissa bits set."
 );
-impl_fpformat!(
-    f64,
-    u64,
-    11,
-    52,
-    f64::from_bits,
-    core::f64::INFINITY,
-    core::f64::MAX_EXP,
-    core::f64::MIN_EXP
+fp8_type!(
+    F8E5M2Fnuz,
+    FP8_E5M2_FNUZ,
+    0x80,
+    "An FP8 E5M2 number with no infinities and no negative zero, stored as its
+bits. Numbers that are too large saturate to ±57344, and the only NaN has the
+bits of negative zero."
 );
+fp8_type!(
+    F8E4M3Fnuz,
+    FP8_E4M3_FNUZ,
+    0x80,
+    "An FP8 E4M3 number with no infinities and no negative zero, stored as its
+bits. Numbers that are too large saturate to ±240, and the only NaN has the
+bits of negative zero."
+);
+
+/// An IEEE 754 binary16 (half precision) number, stored as its bits.
+///
+/// [Equality](trait.FPFormat.html#equality) compares the bits.
+///
+/// ```rust
+/// use hexponent::{FloatLiteral, F16};
+/// let literal: FloatLiteral = "0x1.8p1".parse().unwrap();
+/// let half = literal.convert::<F16>().inner();
+/// assert_eq!(half.to_bits(), 0x4200);
+/// assert_eq!(half.to_f32(), 3.0);
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct F16(u16);
+
+impl F16 {
+    /// Create a number from its bits.
+    pub const fn from_bits(bits: u16) -> F16 {
+        F16(bits)
+    }
+
+    /// Get the bits of the number.
+    pub const fn to_bits(self) -> u16 {
+        self.0
+    }
+
+    /// Convert to an `f32`. This is always exact.
+    pub fn to_f32(self) -> f32 {
+        let sign = ((self.0 & 0x8000) as u32) << 16;
+        let exponent = (self.0 >> 10) & 0x1f;
+        let mantissa = (self.0 & 0x3ff) as u32;
+        let magnitude = match exponent {
+            // Subnormals are a multiple of 2^-24, which is a normal f32.
+            0 => (mantissa as f32 * f32::from_bits((127 - 24) << 23)).to_bits(),
+            0x1f => 0x7f80_0000 | mantissa << 13,
+            _ => ((exponent as u32 + 127 - 15) << 23) | mantissa << 13,
+        };
+        f32::from_bits(sign | magnitude)
+    }
+}
+
+impl ops::Neg for F16 {
+    type Output = F16;
+    fn neg(self) -> F16 {
+        F16(self.0 ^ 0x8000)
+    }
+}
+
+impl From<F16> for f32 {
+    fn from(half: F16) -> f32 {
+        half.to_f32()
+    }
+}
+
+/// A bfloat16 number, stored as its bits. This has the same exponent range as
+/// an `f32`, but only 8 bits of precision.
+///
+/// [Equality](trait.FPFormat.html#equality) compares the bits.
+///
+/// ```rust
+/// use hexponent::{FloatLiteral, BF16};
+/// let literal: FloatLiteral = "0x1.ffp127".parse().unwrap();
+/// let brain_float = literal.convert::<BF16>();
+/// assert!(brain_float.is_imprecise());
+/// assert_eq!(brain_float.inner().to_bits(), 0x7f80);
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BF16(u16);
+
+impl BF16 {
+    /// Create a number from its bits.
+    pub const fn from_bits(bits: u16) -> BF16 {
+        BF16(bits)
+    }
+
+    /// Get the bits of the number.
+    pub const fn to_bits(self) -> u16 {
+        self.0
+    }
+
+    /// Convert to an `f32`. This is always exact.
+    pub fn to_f32(self) -> f32 {
+        // bfloat16 is the top half of an f32.
+        f32::from_bits((self.0 as u32) << 16)
+    }
+}
+
+impl ops::Neg for BF16 {
+    type Output = BF16;
+    fn neg(self) -> BF16 {
+        BF16(self.0 ^ 0x8000)
+    }
+}
+
+impl From<BF16> for f32 {
+    fn from(brain_float: BF16) -> f32 {
+        brain_float.to_f32()
+    }
+}
+
+/// An x87 80-bit extended precision number, stored as its bits. This has a
+/// 15-bit exponent and a 64-bit significand, including an explicit integer bit.
+///
+/// [Equality](trait.FPFormat.html#equality) compares the bits.
+///
+/// ```rust
+/// use hexponent::{FloatLiteral, F80};
+/// let literal: FloatLiteral = "0x1.921fb54442d1846ap+1".parse().unwrap();
+/// let pi = literal.convert::<F80>().inner();
+/// assert_eq!(pi.to_bits(), 0x4000_c90f_daa2_2168_c235);
+/// assert_eq!(pi.to_le_bytes(), [0x35, 0xc2, 0x68, 0x21, 0xa2, 0xda, 0x0f, 0xc9, 0x00, 0x40]);
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct F80(u128);
+
+impl F80 {
+    /// Create a number from its bits. Only the lowest 80 bits are used.
+    pub const fn from_bits(bits: u128) -> F80 {
+        F80(bits & ((1 << 80) - 1))
+    }
+
+    /// Get the bits of the number, in the lowest 80 bits.
+    pub const fn to_bits(self) -> u128 {
+        self.0
+    }
+
+    /// Create a number from its in-memory representation on x86, which is
+    /// little endian.
+    pub fn from_le_bytes(bytes: [u8; 10]) -> F80 {
+        let mut wide = [0; 16];
+        wide[..10].copy_from_slice(&bytes);
+        F80(u128::from_le_bytes(wide))
+    }
+
+    /// Get the in-memory representation of the number on x86, which is little
+    /// endian.
+    pub fn to_le_bytes(self) -> [u8; 10] {
+        let mut bytes = [0; 10];
+        bytes.copy_from_slice(&self.0.to_le_bytes()[..10]);
+        bytes
+    }
+}
+
+impl ops::Neg for F80 {
+    type Output = F80;
+    fn neg(self) -> F80 {
+        F80(self.0 ^ (1 << 79))
+    }
+}
+
+/// An IEEE 754 binary128 (quad precision) number, stored as its bits.
+///
+/// [Equality](trait.FPFormat.html#equality) compares the bits.
+///
+/// ```rust
+/// use hexponent::{FloatLiteral, F128};
+/// let literal: FloatLiteral = "0x1.921fb54442d18469898cc51701b8p+1".parse().unwrap();
+/// let pi = literal.convert::<F128>();
+/// assert!(pi.is_precise());
+/// assert_eq!(pi.inner().to_bits(), 0x4000_921f_b544_42d1_8469_898c_c517_01b8);
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct F128(u128);
+
+impl F128 {
+    /// Create a number from its bits.
+    pub const fn from_bits(bits: u128) -> F128 {
+        F128(bits)
+    }
+
+    /// Get the bits of the number.
+    pub const fn to_bits(self) -> u128 {
+        self.0
+    }
+}
+
+impl ops::Neg for F128 {
+    type Output = F128;
+    fn neg(self) -> F128 {
+        F128(self.0 ^ (1 << 127))
+    }
+}
@@ -0,0 +1,182 @@
+//! IBM System/360 hexadecimal floating point.
+
+use crate::rounding::{Normalized, RoundingDirection, RoundingMode};
+use crate::{Conversion, ConversionFlags, ConversionResult, FloatLiteral};
+
+/// The bias of the 7-bit characteristic, which is a power of 16.
+const CHARACTERISTIC_BIAS: i64 = 64;
+
+/// A hexadecimal floating point format. Numbers are `0.fraction * 16^exponent`,
+/// where the fraction has a non-zero leading digit.
+#[derive(Clone, Copy)]
+struct HexFormat {
+    /// The number of hexadecimal digits in the fraction.
+    fraction_digits: u32,
+}
+
+impl HexFormat {
+    /// Round a literal to this format. The result is the fraction and the
+    /// biased characteristic.
+    fn round(self, literal: &FloatLiteral, mode: RoundingMode) -> Conversion<(u128, i64)> {
+        let fraction_bits = 4 * self.fraction_digits as i64;
+        let min_exponent = -CHARACTERISTIC_BIAS;
+        let max_exponent = CHARACTERISTIC_BIAS - 1;
+
+        let normalized = match Normalized::new(literal) {
+            Some(normalized) => normalized,
+            None => return Conversion::exact((0, 0)),
+        };
+
+        let mut flags = ConversionFlags::default();
+
+        // The leading digit of the fraction holds the top bit, so the precision
+        // in bits depends on where the top bit is in its digit.
+        let mut exponent = (normalized.exponent >> 2) + 1;
+        flags.tiny_before_rounding = exponent < min_exponent;
+        let (mut fraction, mut direction) = normalized
+            .round_to_quantum(4 * exponent - fraction_bits, mode)
+            .expect("a rounded fraction always fits in a u128");
+
+        // Rounding up can carry into the next power of 16.
+        if fraction >> fraction_bits != 0 {
+            fraction >>= 4;
+            exponent += 1;
+        }
+
+        // There are no subnormal numbers, so numbers that are too small round
+        // to either zero or the smallest normal number.
+        if exponent < min_exponent {
+            flags.tiny_after_rounding = true;
+            let (multiple, tiny_direction) = normalized
+                .round_to_quantum(4 * min_exponent - 4, mode)
+                .expect("a rounded fraction always fits in a u128");
+            fraction = multiple << (fraction_bits - 4);
+            exponent = min_exponent;
+            direction = tiny_direction;
+        }
+
+        // There are no infinities either, so numbers that are too large
+        // saturate to the largest number.
+        if exponent > max_exponent {
+            flags.overflow = true;
+            fraction = (1 << fraction_bits) - 1;
+            exponent = max_exponent;
+            direction = RoundingDirection::Down;
+        }
+
+        flags.inexact = direction != RoundingDirection::Exact;
+        flags.underflow = flags.tiny_after_rounding && flags.inexact;
+
+        // Zero always has a characteristic of zero.
+        let characteristic = if fraction == 0 {
+            0
+        } else {
+            exponent + CHARACTERISTIC_BIAS
+        };
+        Conversion {
+            value: (fraction, characteristic),
+            flags,
+            direction,
+        }
+    }
+
+    /// Convert a literal to bits. The extended format is returned as the high
+    /// doubleword followed by the low doubleword.
+    fn to_bits(self, literal: &FloatLiteral, mode: RoundingMode) -> Conversion<u128> {
+        let sign = !literal.is_positive as u128;
+        self.round(literal, mode).map(|(fraction, characteristic)| {
+            let characteristic = characteristic as u128;
+            if self.fraction_digits <= 14 {
+                let fraction_bits = 4 * self.fraction_digits;
+                return (sign << (fraction_bits + 7))
+                    | (characteristic << fraction_bits)
+                    | fraction;
+            }
+
+            // The low doubleword has its own sign and characteristic. The
+            // characteristic is 14 less than the high one, modulo 128, because
+            // the low fraction starts 14 digits later.
+            let low_characteristic = if fraction == 0 {
+                0
+            } else {
+                characteristic.wrapping_sub(14) & 0x7f
+            };
+            let high = (sign << 63) | (characteristic << 56) | (fraction >> 56);
+            let low = (sign << 63) | (low_characteristic << 56) | (fraction & ((1 << 56) - 1));
+            (high << 64) | low
+        })
+    }
+}
+
+macro_rules! hfp_type {
+    ($name:ident, $bits_type:ty, $fraction_digits:expr) => {
+        impl $name {
+            /// Create a number from its bits.
+            pub const fn from_bits(bits: $bits_type) -> $name {
+                $name(bits)
+            }
+
+            /// Get the bits of the number.
+            pub const fn to_bits(self) -> $bits_type {
+                self.0
+            }
+
+            /// Convert a literal to this format, rounding to nearest, ties to
+            /// even.
+            pub fn from_literal(literal: FloatLiteral) -> ConversionResult<$name> {
+                $name::from_literal_rounded(literal, RoundingMode::NearestEven)
+            }
+
+            /// Convert a literal to this format with the given rounding mode.
+            pub fn from_literal_rounded(
+                literal: FloatLiteral,
+                mode: RoundingMode,
+            ) -> ConversionResult<$name> {
+                $name::from_literal_with_flags(literal, mode).result()
+            }
+
+            /// Convert a literal to this format with the given rounding mode
+            /// and report the exception flags. Numbers that are too large
+            /// saturate to the largest number and set the overflow flag.
+            pub fn from_literal_with_flags(
+                literal: FloatLiteral,
+                mode: RoundingMode,
+            ) -> Conversion<$name> {
+                let format = HexFormat {
+                    fraction_digits: $fraction_digits,
+                };
+                format
+                    .to_bits(&literal, mode)
+                    .map(|bits| $name(bits as $bits_type))
+            }
+        }
+    };
+}
+
+/// An IBM hexadecimal floating point short (32-bit) number, stored as its
+/// bits. This has a sign bit, a 7-bit characteristic that is a power of 16
+/// with a bias of 64, and a 6 digit fraction.
+///
+/// ```rust
+/// use hexponent::{FloatLiteral, HfpShort};
+/// let literal: FloatLiteral = "-0x76.a".parse().unwrap();
+/// let short = HfpShort::from_literal(literal).inner();
+/// assert_eq!(short.to_bits(), 0xc276_a000);
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HfpShort(u32);
+
+/// An IBM hexadecimal floating point long (64-bit) number, stored as its bits.
+/// This has the same layout as `HfpShort`, with a 14 digit fraction.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HfpLong(u64);
+
+/// An IBM hexadecimal floating point extended (128-bit) number, stored as its
+/// bits. This is a pair of long numbers with a 28 digit fraction split between
+/// them. The high doubleword, which comes first in memory, is in the high bits.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HfpExtended(u128);
+
+hfp_type!(HfpShort, u32, 6);
+hfp_type!(HfpLong, u64, 14);
+hfp_type!(HfpExtended, u128, 28);
@@ -0,0 +1,48 @@
+//! Conversions to integers and fixed point numbers.
+
+use core::convert::TryFrom;
+
+/// Trait for the primitive integer types that literals can be converted to.
+pub trait Integer: Sized {
+    /// Create an integer from a sign and a magnitude, or return `None` if it
+    /// is out of range. Zero is in range whatever the sign.
+    fn from_sign_magnitude(is_positive: bool, magnitude: u128) -> Option<Self>;
+}
+
+macro_rules! impl_unsigned {
+    ($($int_type:ty),*) => {
+        $(
+            impl Integer for $int_type {
+                fn from_sign_magnitude(is_positive: bool, magnitude: u128) -> Option<$int_type> {
+                    if !is_positive && magnitude != 0 {
+                        return None;
+                    }
+                    <$int_type>::try_from(magnitude).ok()
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_signed {
+    ($($int_type:ty),*) => {
+        $(
+            impl Integer for $int_type {
+                fn from_sign_magnitude(is_positive: bool, magnitude: u128) -> Option<$int_type> {
+                    let value = if is_positive {
+                        i128::try_from(magnitude).ok()?
+                    } else if magnitude <= 1 << 127 {
+                        // This wraps to `i128::MIN` for a magnitude of 2^127.
+                        (magnitude as i128).wrapping_neg()
+                    } else {
+                        return None;
+                    };
+                    <$int_type>::try_from(value).ok()
+                }
+            }
+        )*
+    };
+}
+
+impl_unsigned!(u8, u16, u32, u64, u128, usize);
+impl_signed!(i8, i16, i32, i64, i128, isize);
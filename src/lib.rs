@@ -14,13 +14,15 @@
 //! let value = float_repr.convert::<f32>().inner();
 //! assert_eq!(value, 3.25);
 //! ```
-//! Hexponent has a minimum supported rust version of 1.34.
+//! Hexponent has a minimum supported rust version of 1.42. It is set as the
+//! `rust-version` in `Cargo.toml`, so clippy doesn't suggest anything newer.
 //!
 //! ## Features
 //! - No dependencies
 //! - Non-UTF-8 parser
+//! - Hexadecimal formatting, matching `printf("%a")`
 //! - Precision warnings
-//! - `no_std` support (MSRV 1.36.0)
+//! - `no_std` support
 //!
 //! ## Differences from the specification
 //! There are two places where hexponent differs from the C11 specificaiton.
@@ -33,21 +35,53 @@
 //! ```toml
 //! hexponent = {version = "0.2", default-features = false}
 //! ```
-//! `no_std` support is only possible in rustc version 1.36.0 and higher.
 //!
 //! Disabling the `std` feature currently only disables the `std::error::Error`
-//! implementation for `ParseError`.
+//! implementations for the error types: `ParseError`, `OverflowError`,
+//! `BlockSizeError` and `IntegerError`.
 
 #[cfg(not(feature = "std"))]
 extern crate alloc;
 
 #[cfg(not(feature = "std"))]
-use alloc::vec::Vec;
+use alloc::{string::String, vec::Vec};
 
 use core::fmt;
 
+mod decimal;
+use decimal::Decimal;
+
+mod descriptor;
+pub use descriptor::FormatDescriptor;
+
+mod exact;
+use exact::{BigUint, Dyadic};
+pub use exact::{ExactFraction, ExactValue, Limb};
+
+mod format;
+pub use format::{format_f32_exact, format_f64_exact, HexFormatOptions, SubnormalStyle};
+
 mod fpformat;
-pub use fpformat::FPFormat;
+pub use fpformat::{F8E4M3Fnuz, F8E5M2Fnuz, FPFormat, BF16, F128, F16, F80, F8E4M3, F8E5M2};
+
+mod hfp;
+pub use hfp::{HfpExtended, HfpLong, HfpShort};
+
+mod integer;
+pub use integer::Integer;
+
+mod mx;
+pub use mx::{MxBlock, MxElement, MX_BLOCK_SIZE};
+
+mod posit;
+pub use posit::{Posit16, Posit32, Posit8, PositEs};
+
+mod rounding;
+use rounding::Normalized;
+pub use rounding::{RoundingDirection, RoundingMode};
+
+mod vax;
+pub use vax::{VaxD, VaxF, VaxG};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 /// Indicates the precision of a conversion
@@ -55,9 +89,6 @@ pub enum ConversionResult<T> {
     /// The conversion was precise and the result represents the original exactly.
     Precise(T),
 
-    // TODO: I should be able to calculate how imprecise the conversion is too,
-    // which might be useful. This also might allow some subnormal numbers to be
-    // returned as precise results.
     /// The conversion was imprecise and the result is as close to the original
     /// as possible.
     Imprecise(T),
@@ -81,6 +112,206 @@ impl<T> ConversionResult<T> {
     pub fn is_imprecise(&self) -> bool {
         matches!(self, ConversionResult::Imprecise(_))
     }
+
+    /// Apply a function to the contained value, keeping the precision.
+    ///
+    /// ```rust
+    /// use hexponent::{ConversionResult, FloatLiteral};
+    /// let literal: FloatLiteral = "0x1.8".parse().unwrap();
+    /// let bits = literal.convert::<f32>().map(f32::to_bits);
+    /// assert_eq!(bits, ConversionResult::Precise(0x3fc0_0000));
+    /// ```
+    pub fn map<U, F: FnOnce(T) -> U>(self, f: F) -> ConversionResult<U> {
+        match self {
+            ConversionResult::Precise(value) => ConversionResult::Precise(f(value)),
+            ConversionResult::Imprecise(value) => ConversionResult::Imprecise(f(value)),
+        }
+    }
+}
+
+/// The IEEE 754 exception flags raised by a conversion.
+///
+/// Tininess is reported both before and after rounding, because IEEE 754
+/// leaves the choice to the implementation. The `underflow` flag uses tininess
+/// after rounding.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ConversionFlags {
+    /// The result is not exactly equal to the literal.
+    pub inexact: bool,
+    /// The literal is too large for the format. The result is either infinity
+    /// or the largest finite number, depending on the rounding mode.
+    pub overflow: bool,
+    /// The result is both tiny (after rounding) and inexact.
+    pub underflow: bool,
+    /// The literal is non-zero and smaller in magnitude than the smallest
+    /// normal number.
+    pub tiny_before_rounding: bool,
+    /// The literal, rounded as if the exponent range were unbounded, is non-zero
+    /// and smaller in magnitude than the smallest normal number.
+    pub tiny_after_rounding: bool,
+}
+
+/// The result of a conversion with the exception flags it raised.
+///
+/// ```rust
+/// use hexponent::{FloatLiteral, RoundingDirection, RoundingMode};
+/// let literal: FloatLiteral = "0x1p200".parse().unwrap();
+/// let conversion = literal.convert_with_flags::<f32>(RoundingMode::NearestEven);
+/// assert!(conversion.flags.overflow);
+/// assert_eq!(conversion.value, f32::INFINITY);
+/// assert_eq!(conversion.direction, RoundingDirection::Up);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Conversion<T> {
+    /// The converted value.
+    pub value: T,
+    /// The exception flags raised by the conversion.
+    pub flags: ConversionFlags,
+    /// The direction the value was rounded in, in terms of magnitude.
+    pub direction: RoundingDirection,
+}
+
+impl<T> Conversion<T> {
+    /// A conversion that was exact and raised no flags.
+    pub(crate) fn exact(value: T) -> Conversion<T> {
+        Conversion {
+            value,
+            flags: ConversionFlags::default(),
+            direction: RoundingDirection::Exact,
+        }
+    }
+
+    /// Convert the value, keeping the flags and direction.
+    pub(crate) fn map<U, F: FnOnce(T) -> U>(self, f: F) -> Conversion<U> {
+        Conversion {
+            value: f(self.value),
+            flags: self.flags,
+            direction: self.direction,
+        }
+    }
+
+    /// Convert to a `ConversionResult`, which only keeps track of whether the
+    /// conversion was exact.
+    pub fn result(self) -> ConversionResult<T> {
+        if self.flags.inexact {
+            ConversionResult::Imprecise(self.value)
+        } else {
+            ConversionResult::Precise(self.value)
+        }
+    }
+}
+
+/// The exact error of converting a literal to a floating point format.
+///
+/// ```rust
+/// use hexponent::{FloatLiteral, RoundingMode};
+/// let literal: FloatLiteral = "0x1.000001p0".parse().unwrap();
+/// let error = literal.rounding_error::<f32>(RoundingMode::NearestEven).unwrap();
+/// let difference = error.difference.unwrap();
+/// assert_eq!(difference.convert::<f64>().inner(), -(2f64.powi(-24)));
+/// assert_eq!(error.ulps.unwrap().convert::<f64>().inner(), -0.5);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RoundingError {
+    /// The converted value minus the literal, exactly. This is `None` if the
+    /// literal and the converted value are more than `MAX_EXACT_GAP` binary
+    /// orders of magnitude apart, where the exact difference would need at
+    /// least that many bits.
+    pub difference: Option<FloatLiteral>,
+    /// The difference in units in the last place of the converted value,
+    /// exactly. A conversion that rounds to nearest is always within half an
+    /// ulp. This is `None` when `difference` is.
+    pub ulps: Option<FloatLiteral>,
+    /// An upper bound of the relative error, `|difference / literal|`. This is
+    /// zero when the literal is zero.
+    pub relative_bound: f64,
+}
+
+impl RoundingError {
+    /// The largest number of bits between the literal and the converted value
+    /// for which the difference is calculated exactly.
+    pub const MAX_EXACT_GAP: i64 = 1 << 16;
+
+    /// Calculate the error of converting `literal` to `mantissa * 2^quantum`,
+    /// where `2^quantum` is the size of an ulp.
+    pub(crate) fn new(literal: &FloatLiteral, mantissa: u128, quantum: i64) -> RoundingError {
+        let exact = Dyadic::from_literal(literal).normalize();
+        let converted = Dyadic {
+            is_positive: literal.is_positive,
+            mantissa: BigUint::from_u128(mantissa),
+            exponent: quantum,
+        }
+        .normalize();
+
+        // Aligning the values for the subtraction takes as many bits as there
+        // are between them, so check that first. Past the limit, the literal
+        // is either so far above the converted value that the relative error
+        // is just below 1, or so far below it that it is huge.
+        if !exact.mantissa.is_zero() && !converted.mantissa.is_zero() {
+            let exact_top = exact.exponent + exact.mantissa.bit_length() as i64;
+            let converted_top = converted.exponent + converted.mantissa.bit_length() as i64;
+            let gap = (exact.exponent - converted_top).max(converted.exponent - exact_top);
+            if gap > RoundingError::MAX_EXACT_GAP {
+                let relative_bound = if exact_top > converted_top {
+                    1.0
+                } else {
+                    core::f64::INFINITY
+                };
+                return RoundingError {
+                    difference: None,
+                    ulps: None,
+                    relative_bound,
+                };
+            }
+        }
+
+        let difference = converted.sub(&exact);
+        let ulps = Dyadic {
+            exponent: difference.exponent - quantum,
+            ..difference.clone()
+        };
+
+        let relative_bound = match Normalized::new(literal) {
+            Some(normalized) if !difference.mantissa.is_zero() => {
+                // Scale both values so that the literal is between 1 and 2,
+                // which keeps them in the range of an f64.
+                let error = Dyadic {
+                    is_positive: true,
+                    mantissa: difference.mantissa.clone(),
+                    exponent: difference.exponent - normalized.exponent,
+                };
+                let magnitude = Dyadic {
+                    is_positive: true,
+                    mantissa: exact.mantissa,
+                    exponent: exact.exponent - normalized.exponent,
+                };
+                let upper: f64 = error
+                    .to_literal()
+                    .convert_rounded(RoundingMode::TowardPositive)
+                    .inner();
+                let lower: f64 = magnitude
+                    .to_literal()
+                    .convert_rounded(RoundingMode::TowardNegative)
+                    .inner();
+                // The division is off by at most half an ulp, so stepping up
+                // one ulp keeps the result above the exact quotient. A
+                // quotient that overflowed is already an upper bound.
+                let quotient = upper / lower;
+                if quotient.is_infinite() {
+                    core::f64::INFINITY
+                } else {
+                    f64::from_bits(quotient.to_bits() + 1)
+                }
+            }
+            _ => 0.0,
+        };
+
+        RoundingError {
+            difference: Some(difference.to_literal()),
+            ulps: Some(ulps.to_literal()),
+            relative_bound,
+        }
+    }
 }
 
 /// Error type for parsing hexadecimal literals.
@@ -148,7 +379,78 @@ impl fmt::Display for ParseError {
 /// Only available with the `std` feature.
 impl std::error::Error for ParseError {}
 
-use std::iter::{Fuse, Peekable};
+/// Error type for conversions to formats that can't represent numbers that are
+/// too large, such as fixed point numbers or the VAX floating point formats.
+///
+/// `OverflowError` only implements `std::error::Error` when the `std` feature
+/// is enabled.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct OverflowError;
+
+impl fmt::Display for OverflowError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "literal is too large for the format")
+    }
+}
+
+#[cfg(feature = "std")]
+/// Only available with the `std` feature.
+impl std::error::Error for OverflowError {}
+
+/// Error type for MX blocks with more than `MX_BLOCK_SIZE` literals.
+///
+/// `BlockSizeError` only implements `std::error::Error` when the `std`
+/// feature is enabled.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct BlockSizeError {
+    /// The number of literals that were given.
+    pub len: usize,
+}
+
+impl fmt::Display for BlockSizeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "an MX block has at most {} elements, but {} were given",
+            MX_BLOCK_SIZE, self.len
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+/// Only available with the `std` feature.
+impl std::error::Error for BlockSizeError {}
+
+/// Error type for exact conversions to integers.
+///
+/// `IntegerError` only implements `std::error::Error` when the `std` feature
+/// is enabled.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum IntegerError {
+    /// The literal has a fractional part.
+    ///
+    /// Example: `0x1.8`
+    NotAnInteger,
+    /// The literal is an integer, but it doesn't fit in the integer type.
+    ///
+    /// Example: `0x100` as a `u8`
+    Overflow,
+}
+
+impl fmt::Display for IntegerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IntegerError::NotAnInteger => write!(f, "literal is not an integer"),
+            IntegerError::Overflow => write!(f, "literal is too large for the integer type"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+/// Only available with the `std` feature.
+impl std::error::Error for IntegerError {}
+
+use core::iter::{Fuse, Peekable};
 
 /// An iterator that counts the number of chars consumed.
 pub struct CharsIterator<Chars>
@@ -211,12 +513,250 @@ pub struct FloatLiteral {
 }
 
 impl FloatLiteral {
-    /// Convert the `self` to an `f32` or `f64` and return the precision of the
-    /// conversion.
+    /// Convert the `self` to a floating point format, such as `f32`, `f64` or
+    /// `F16`, and return the precision of the conversion.
     pub fn convert<F: FPFormat>(self) -> ConversionResult<F> {
         F::from_literal(self)
     }
 
+    /// Convert the `self` to a floating point format using the given rounding
+    /// mode and return the precision of the conversion.
+    ///
+    /// ```rust
+    /// use hexponent::{FloatLiteral, RoundingMode};
+    /// let literal: FloatLiteral = "0x1.000001p0".parse().unwrap();
+    /// let lower = literal.clone().convert_rounded::<f32>(RoundingMode::TowardNegative);
+    /// let upper = literal.convert_rounded::<f32>(RoundingMode::TowardPositive);
+    /// assert_eq!(lower.inner(), 1.0);
+    /// assert_eq!(upper.inner(), 1.0 + f32::EPSILON);
+    /// ```
+    pub fn convert_rounded<F: FPFormat>(self, mode: RoundingMode) -> ConversionResult<F> {
+        F::from_literal_rounded(self, mode)
+    }
+
+    /// Convert the `self` to a floating point format using the given rounding
+    /// mode and return the IEEE 754 exception flags raised by the conversion.
+    pub fn convert_with_flags<F: FPFormat>(self, mode: RoundingMode) -> Conversion<F> {
+        F::from_literal_with_flags(self, mode)
+    }
+
+    /// Calculate the exact error of converting `self` to a floating point format
+    /// with the given rounding mode. Returns `None` if the literal overflows to
+    /// infinity, where the error is not finite.
+    pub fn rounding_error<F: FPFormat>(&self, mode: RoundingMode) -> Option<RoundingError> {
+        F::rounding_error(self, mode)
+    }
+
+    /// Convert `self` to a fixed point number with `fraction_bits` bits after
+    /// the binary point, using the given rounding mode. The result is the raw
+    /// integer, which is the value multiplied by `2^fraction_bits`. Returns an
+    /// error if the rounded value doesn't fit in the integer type.
+    ///
+    /// ```rust
+    /// use hexponent::{FloatLiteral, RoundingMode};
+    /// // Q15, with 15 fraction bits in an `i16`.
+    /// let literal: FloatLiteral = "-0x.c".parse().unwrap();
+    /// let q15 = literal.to_fixed::<i16>(15, RoundingMode::NearestEven);
+    /// assert_eq!(q15.unwrap().inner(), -0x6000);
+    ///
+    /// let literal: FloatLiteral = "0x1".parse().unwrap();
+    /// assert!(literal.to_fixed::<i16>(15, RoundingMode::NearestEven).is_err());
+    /// ```
+    pub fn to_fixed<I: Integer>(
+        &self,
+        fraction_bits: u32,
+        mode: RoundingMode,
+    ) -> Result<ConversionResult<I>, OverflowError> {
+        let (magnitude, direction) = match Normalized::new(self) {
+            Some(normalized) => normalized
+                .round_to_quantum(-(fraction_bits as i64), mode)
+                .ok_or(OverflowError)?,
+            None => (0, RoundingDirection::Exact),
+        };
+        let value = I::from_sign_magnitude(self.is_positive, magnitude).ok_or(OverflowError)?;
+        if direction == RoundingDirection::Exact {
+            Ok(ConversionResult::Precise(value))
+        } else {
+            Ok(ConversionResult::Imprecise(value))
+        }
+    }
+
+    /// Convert `self` to an integer, if it is an integer that is in range for
+    /// the integer type.
+    ///
+    /// ```rust
+    /// use hexponent::{FloatLiteral, IntegerError};
+    /// let literal: FloatLiteral = "0xDEADBEEF".parse().unwrap();
+    /// assert_eq!(literal.to_integer::<u32>(), Ok(0xdead_beef));
+    /// assert_eq!(literal.to_integer::<i32>(), Err(IntegerError::Overflow));
+    ///
+    /// let literal: FloatLiteral = "0x1.8".parse().unwrap();
+    /// assert_eq!(literal.to_integer::<u32>(), Err(IntegerError::NotAnInteger));
+    /// ```
+    pub fn to_integer<I: Integer>(&self) -> Result<I, IntegerError> {
+        match self.to_fixed(0, RoundingMode::TowardZero) {
+            Ok(ConversionResult::Precise(value)) => Ok(value),
+            Ok(ConversionResult::Imprecise(_)) => Err(IntegerError::NotAnInteger),
+            Err(OverflowError) => Err(IntegerError::Overflow),
+        }
+    }
+
+    /// Convert `self` to an integer, rounding toward negative infinity.
+    /// Returns an error if the rounded value is out of range.
+    pub fn to_integer_floor<I: Integer>(&self) -> Result<ConversionResult<I>, OverflowError> {
+        self.to_fixed(0, RoundingMode::TowardNegative)
+    }
+
+    /// Convert `self` to an integer, rounding toward positive infinity.
+    /// Returns an error if the rounded value is out of range.
+    pub fn to_integer_ceil<I: Integer>(&self) -> Result<ConversionResult<I>, OverflowError> {
+        self.to_fixed(0, RoundingMode::TowardPositive)
+    }
+
+    /// Convert `self` to an integer, rounding toward zero. Returns an error if
+    /// the rounded value is out of range.
+    pub fn to_integer_trunc<I: Integer>(&self) -> Result<ConversionResult<I>, OverflowError> {
+        self.to_fixed(0, RoundingMode::TowardZero)
+    }
+
+    /// Convert `self` to an integer, rounding to nearest with ties to even.
+    /// Returns an error if the rounded value is out of range.
+    pub fn to_integer_nearest<I: Integer>(&self) -> Result<ConversionResult<I>, OverflowError> {
+        self.to_fixed(0, RoundingMode::NearestEven)
+    }
+
+    /// Get the exact value of `self` as a sign, an integer mantissa and a
+    /// binary exponent.
+    pub fn to_exact<L: Limb>(&self) -> ExactValue<L> {
+        let exact = Dyadic::from_literal(self).normalize();
+        ExactValue {
+            is_positive: exact.is_positive,
+            mantissa: exact.mantissa.to_limbs(),
+            exponent: exact.exponent,
+        }
+    }
+
+    /// Get the exact value of `self` as a sign, a numerator and a denominator.
+    ///
+    /// The numerator or the denominator has about as many bits as the binary
+    /// exponent, so this takes time and memory proportional to it. For example,
+    /// `0x1p-2147483648` has a 256 MiB denominator. Use `to_exact` to get the
+    /// same value without expanding the exponent.
+    pub fn to_fraction<L: Limb>(&self) -> ExactFraction<L> {
+        let exact = Dyadic::from_literal(self).normalize();
+        let one = BigUint::from_u128(1);
+        let (numerator, denominator) = if exact.exponent >= 0 {
+            (exact.mantissa.shl(exact.exponent as u64), one)
+        } else {
+            (exact.mantissa, one.shl((-exact.exponent) as u64))
+        };
+        ExactFraction {
+            is_positive: exact.is_positive,
+            numerator: numerator.to_limbs(),
+            denominator: denominator.to_limbs(),
+        }
+    }
+
+    /// Get the exact decimal expansion of `self`. Every hexadecimal literal has
+    /// a finite decimal expansion, although literals with very large or very
+    /// small exponents have a lot of digits. There are about 0.3 digits for
+    /// each bit of the exponent, and this takes time proportional to the square
+    /// of the number of digits, so use `to_decimal_exponential` for those.
+    ///
+    /// ```rust
+    /// use hexponent::FloatLiteral;
+    /// let literal: FloatLiteral = "0x1.99999ap-4".parse().unwrap();
+    /// assert_eq!(literal.to_decimal_string(), "0.100000001490116119384765625");
+    /// ```
+    pub fn to_decimal_string(&self) -> String {
+        Decimal::from_literal(self).to_positional()
+    }
+
+    /// Format `self` in decimal with one digit before the decimal point and
+    /// `precision` digits after it, rounded with the given rounding mode. This
+    /// matches `printf("%.*e")`, which rounds to nearest, ties to even.
+    ///
+    /// Only the digits that are written are computed, so this is fast even for
+    /// the largest and smallest exponents.
+    ///
+    /// ```rust
+    /// use hexponent::{FloatLiteral, RoundingMode};
+    /// let literal: FloatLiteral = "0x1.99999ap-4".parse().unwrap();
+    /// let decimal = literal.to_decimal_exponential(10, RoundingMode::NearestEven);
+    /// assert_eq!(decimal, "1.0000000149e-01");
+    /// ```
+    pub fn to_decimal_exponential(&self, precision: usize, mode: RoundingMode) -> String {
+        Decimal::from_dyadic_with_precision(&Dyadic::from_literal(self), precision)
+            .to_exponential(precision, mode)
+    }
+
+    /// Format `self` in decimal with `precision` digits after the decimal
+    /// point, rounded with the given rounding mode. This matches
+    /// `printf("%.*f")`, which rounds to nearest, ties to even.
+    ///
+    /// Only the digits that are written are computed, so numbers below
+    /// `10^-precision` are fast, but large numbers have as many digits as
+    /// `to_decimal_string`.
+    ///
+    /// ```rust
+    /// use hexponent::{FloatLiteral, RoundingMode};
+    /// let literal: FloatLiteral = "-0x1.8p-1".parse().unwrap();
+    /// assert_eq!(literal.to_decimal_fixed(0, RoundingMode::NearestEven), "-1");
+    /// assert_eq!(literal.to_decimal_fixed(3, RoundingMode::TowardZero), "-0.750");
+    /// ```
+    pub fn to_decimal_fixed(&self, precision: usize, mode: RoundingMode) -> String {
+        let place = -(precision as i64) - 1;
+        Decimal::from_dyadic_to_place(&Dyadic::from_literal(self), place).to_fixed(precision, mode)
+    }
+
+    /// Get the shortest decimal that converts to the same value as `self` in
+    /// the format `F`, rounding to nearest, ties to even. If more than one
+    /// decimal has the fewest digits, the one closest to the converted value is
+    /// chosen, with ties away from zero. The decimal is in scientific notation,
+    /// and matches the `LowerExp` formatting of `f32` and `f64`. Returns `None`
+    /// if `self` converts to infinity.
+    ///
+    /// ```rust
+    /// use hexponent::FloatLiteral;
+    /// let literal: FloatLiteral = "0x1.99999ap-4".parse().unwrap();
+    /// assert_eq!(literal.to_shortest_decimal::<f32>().unwrap(), "1e-1");
+    /// assert_eq!(literal.to_shortest_decimal::<f64>().unwrap(), "1.0000000149011612e-1");
+    /// ```
+    pub fn to_shortest_decimal<F: FPFormat>(&self) -> Option<String> {
+        let interval = fpformat::layout::<F>().rounding_interval(self)?;
+        Some(Decimal::shortest(&interval).to_scientific())
+    }
+
+    /// Check whether `decimal` is a shortest decimal for `self` in the format
+    /// `F`: it converts to the same value as `self`, rounding to nearest, ties
+    /// to even, and no decimal with fewer significant digits does. The decimal
+    /// can be in positional or scientific notation, like `0.1` or `1e-1`, and
+    /// leading and trailing zeros aren't significant. Returns `false` if
+    /// `decimal` isn't a decimal number, or if `self` converts to infinity.
+    ///
+    /// ```rust
+    /// use hexponent::FloatLiteral;
+    /// let literal: FloatLiteral = "0x1.99999ap-4".parse().unwrap();
+    /// assert!(literal.is_shortest_decimal::<f32>("0.1"));
+    /// assert!(literal.is_shortest_decimal::<f32>("0.10"));
+    /// assert!(!literal.is_shortest_decimal::<f32>("0.100000001"));
+    /// assert!(!literal.is_shortest_decimal::<f64>("0.1"));
+    /// ```
+    pub fn is_shortest_decimal<F: FPFormat>(&self, decimal: &str) -> bool {
+        let decimal = match Decimal::parse(decimal) {
+            Some(decimal) => decimal,
+            None => return false,
+        };
+        let interval = match fpformat::layout::<F>().rounding_interval(self) {
+            Some(interval) => interval,
+            None => return false,
+        };
+        let shortest = Decimal::shortest(&interval);
+        decimal.is_positive == shortest.is_positive
+            && decimal.len() == shortest.len()
+            && decimal.is_in(&interval)
+    }
+
     /// Helper used by the tests.
     #[cfg(test)]
     pub fn create(is_positive: bool, digits: Vec<u8>, decimal_offset: i32, exponent: i32) -> Self {
@@ -341,6 +881,25 @@ impl FloatLiteral {
             exponent,
         })
     }
+
+    /// Parse a literal from the start of a byte slice, which doesn't need to
+    /// be UTF-8. Returns the literal and the number of bytes consumed, and
+    /// anything after the literal is ignored.
+    ///
+    /// ```rust
+    /// use hexponent::FloatLiteral;
+    /// let (literal, consumed) = FloatLiteral::from_bytes(b"0x1.8p1\xff\xfe").unwrap();
+    /// assert_eq!(literal.convert::<f32>().inner(), 3.0);
+    /// assert_eq!(consumed, 7);
+    /// ```
+    pub fn from_bytes(data: &[u8]) -> Result<(FloatLiteral, usize), ParseError> {
+        // Every byte becomes one char. Bytes that aren't ASCII never match
+        // anything in a literal, so they end it like any other character.
+        let mut consumed = 0;
+        let chars = data.iter().map(|&byte| char::from(byte));
+        let literal = FloatLiteral::from_chars(chars, '.', &mut consumed)?;
+        Ok((literal, consumed))
+    }
 }
 
 /// Parse a hex float from a sequence of Chars with the given decimal separator.
@@ -363,6 +922,98 @@ impl core::str::FromStr for FloatLiteral {
     }
 }
 
+/// Two literals are equal if they have the same sign and value, however they
+/// are written. `0x1p4` and `0x10` are equal, but `0x0` and `-0x0` are not.
+impl PartialEq for FloatLiteral {
+    fn eq(&self, other: &FloatLiteral) -> bool {
+        let a = Dyadic::from_literal(self).normalize();
+        let b = Dyadic::from_literal(other).normalize();
+        a.is_positive == b.is_positive && a.mantissa == b.mantissa && a.exponent == b.exponent
+    }
+}
+
+impl Eq for FloatLiteral {}
+
+impl FloatLiteral {
+    /// Write the digits where they are, with the point at `decimal_offset`.
+    fn fmt_grouped(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sign = if self.is_positive { "" } else { "-" };
+        write!(f, "{}0x", sign)?;
+        let decimal_offset = self.decimal_offset as i64;
+        let digit = |index: i64| {
+            let value = if index < self.digits.len() as i64 {
+                self.digits[index as usize]
+            } else {
+                0
+            };
+            core::char::from_digit(value as u32, 16).unwrap()
+        };
+        if decimal_offset <= 0 {
+            f.write_str("0")?;
+        } else {
+            for index in 0..decimal_offset {
+                write!(f, "{}", digit(index))?;
+            }
+        }
+        if (self.digits.len() as i64) > decimal_offset {
+            f.write_str(".")?;
+            for _ in decimal_offset..0 {
+                f.write_str("0")?;
+            }
+            for index in decimal_offset.max(0)..self.digits.len() as i64 {
+                write!(f, "{}", digit(index))?;
+            }
+        }
+        write!(f, "p{:+}", self.exponent)
+    }
+}
+
+/// The default form is normalized, with a leading `1` and a binary exponent,
+/// like `-0x1.8p+3`. The alternate form, `{:#}`, keeps the digits and
+/// exponent as they were parsed, like `-0x18p-1`, although leading and
+/// trailing zeros are not kept. Either form parses back to an equal literal.
+///
+/// ```rust
+/// use hexponent::FloatLiteral;
+/// let literal: FloatLiteral = "-0x0018.0p-1".parse().unwrap();
+/// assert_eq!(literal.to_string(), "-0x1.8p+3");
+/// assert_eq!(format!("{:#}", literal), "-0x18p-1");
+/// assert_eq!(literal.to_string().parse::<FloatLiteral>().unwrap(), literal);
+/// ```
+impl fmt::Display for FloatLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.alternate() {
+            return self.fmt_grouped(f);
+        }
+
+        let exact = Dyadic::from_literal(self).normalize();
+        if exact.mantissa.is_zero() {
+            let sign = if self.is_positive { "" } else { "-" };
+            return write!(f, "{}0x0p+0", sign);
+        }
+
+        // Pad the bits after the leading one to a whole number of digits.
+        let fraction_bits = exact.mantissa.bit_length() - 1;
+        let exponent = exact.exponent + fraction_bits as i64;
+        if exponent < core::i32::MIN as i64 || exponent > core::i32::MAX as i64 {
+            // The exponent can't be parsed, so move some of it into the digits.
+            return Dyadic::from_literal(self).to_literal().fmt_grouped(f);
+        }
+        let padding = (4 - fraction_bits % 4) % 4;
+        let digits = exact.mantissa.shl(padding).to_hex_digits();
+
+        let sign = if self.is_positive { "" } else { "-" };
+        write!(f, "{}0x1", sign)?;
+        if digits.len() > 1 {
+            f.write_str(".")?;
+            for &digit in &digits[1..] {
+                write!(f, "{:x}", digit)?;
+            }
+        }
+        write!(f, "p{:+}", exponent)
+    }
+}
+
 impl From<FloatLiteral> for f32 {
     fn from(literal: FloatLiteral) -> f32 {
         literal.convert().inner()
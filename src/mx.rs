@@ -0,0 +1,113 @@
+//! OCP Microscaling (MX) block conversion.
+
+use crate::fpformat::{FP4_E2M1, FP6_E2M3, FP6_E3M2, FP8_E4M3, FP8_E5M2};
+use crate::rounding::{Normalized, RoundingMode};
+use crate::{BlockSizeError, FloatLiteral, FormatDescriptor};
+
+/// The number of elements in an MX block.
+pub const MX_BLOCK_SIZE: usize = 32;
+
+/// The element formats of an MX block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MxElement {
+    /// FP8 E4M3, as in `F8E4M3`.
+    Fp8E4M3,
+    /// FP8 E5M2, as in `F8E5M2`.
+    Fp8E5M2,
+    /// FP6 E3M2, with a bias of 3 and no infinities or NaNs.
+    Fp6E3M2,
+    /// FP6 E2M3, with a bias of 1 and no infinities or NaNs.
+    Fp6E2M3,
+    /// FP4 E2M1, with a bias of 1 and no infinities or NaNs.
+    Fp4E2M1,
+}
+
+impl MxElement {
+    fn format(self) -> FormatDescriptor {
+        match self {
+            MxElement::Fp8E4M3 => FP8_E4M3,
+            MxElement::Fp8E5M2 => FP8_E5M2,
+            MxElement::Fp6E3M2 => FP6_E3M2,
+            MxElement::Fp6E2M3 => FP6_E2M3,
+            MxElement::Fp4E2M1 => FP4_E2M1,
+        }
+    }
+}
+
+/// An OCP Microscaling (MX) block: 32 elements that share a power of two
+/// scale.
+///
+/// ```rust
+/// use hexponent::{FloatLiteral, MxBlock, MxElement};
+/// let literals: Vec<FloatLiteral> = ["0x1p10", "0x1.8p9", "-0x1p-20"]
+///     .iter()
+///     .map(|s| s.parse().unwrap())
+///     .collect();
+/// let block = MxBlock::from_literals(&literals, MxElement::Fp8E4M3).unwrap();
+/// // 2^10 is scaled down to 2^8, the largest power of two in FP8 E4M3.
+/// assert_eq!(block.scale, 127 + 2);
+/// assert_eq!(&block.elements[..3], &[0x78, 0x74, 0x80]);
+/// assert_eq!(&block.inexact[..3], &[false, false, true]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MxBlock {
+    /// The format of the elements.
+    pub element: MxElement,
+    /// The shared scale, as an E8M0 value. This is a power of two exponent
+    /// with a bias of 127.
+    pub scale: u8,
+    /// The bits of each element, in the lowest bits of each byte.
+    pub elements: [u8; MX_BLOCK_SIZE],
+    /// Whether each element was rounded.
+    pub inexact: [bool; MX_BLOCK_SIZE],
+}
+
+impl MxBlock {
+    /// Convert literals to an MX block. If there are fewer than 32 literals,
+    /// the remaining elements are zero.
+    ///
+    /// The shared scale is chosen from the literal with the largest magnitude,
+    /// so that it lands in the largest binade of the element format. Each
+    /// element is then rounded to nearest, ties to even, and elements that
+    /// are too large saturate to the largest finite value.
+    ///
+    /// Returns an error if there are more than 32 literals.
+    pub fn from_literals(
+        literals: &[FloatLiteral],
+        element: MxElement,
+    ) -> Result<MxBlock, BlockSizeError> {
+        if literals.len() > MX_BLOCK_SIZE {
+            return Err(BlockSizeError {
+                len: literals.len(),
+            });
+        }
+        let format = element.format();
+
+        // An all zero block gets the smallest scale.
+        let max_exponent = literals
+            .iter()
+            .filter_map(|literal| Normalized::new(literal).map(|n| n.exponent))
+            .max();
+        let scale_exponent = match max_exponent {
+            Some(exponent) => (exponent - format.max_exponent()).max(-127).min(127),
+            None => -127,
+        };
+
+        let mut elements = [0; MX_BLOCK_SIZE];
+        let mut inexact = [false; MX_BLOCK_SIZE];
+        for (index, literal) in literals.iter().enumerate() {
+            let mut scaled = literal.clone();
+            scaled.exponent = scaled.exponent.saturating_sub(scale_exponent as i32);
+            let conversion = format.convert_bits_saturating(&scaled, RoundingMode::NearestEven);
+            elements[index] = conversion.value as u8;
+            inexact[index] = conversion.flags.inexact;
+        }
+
+        Ok(MxBlock {
+            element,
+            scale: (scale_exponent + 127) as u8,
+            elements,
+            inexact,
+        })
+    }
+}
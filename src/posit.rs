@@ -0,0 +1,146 @@
+//! Posits, also known as type III unums.
+
+use crate::rounding::{Normalized, RoundingDirection, RoundingMode};
+use crate::{ConversionResult, FloatLiteral};
+
+/// The number of exponent bits of a posit, which is called `es`. The 2022
+/// posit standard always uses 2, while older drafts use 0 for 8-bit posits, 1
+/// for 16-bit posits and 2 for 32-bit posits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PositEs {
+    /// No exponent bits, so `useed` is 2.
+    Es0,
+    /// One exponent bit, so `useed` is 4.
+    Es1,
+    /// Two exponent bits, so `useed` is 16.
+    Es2,
+}
+
+impl PositEs {
+    fn bits(self) -> u32 {
+        match self {
+            PositEs::Es0 => 0,
+            PositEs::Es1 => 1,
+            PositEs::Es2 => 2,
+        }
+    }
+}
+
+/// Convert a literal to the bits of an `n` bit posit with `es` exponent bits.
+///
+/// Posits are rounded in their encoding, to nearest with ties to even, and
+/// never round to zero or NaR.
+fn to_posit_bits(literal: &FloatLiteral, n: u32, es: PositEs) -> ConversionResult<u128> {
+    let es = es.bits();
+    let normalized = match Normalized::new(literal) {
+        Some(normalized) => normalized,
+        None => return ConversionResult::Precise(0),
+    };
+
+    // The scale is split into the regime, which counts powers of
+    // `useed = 2^(2^es)`, and the exponent, which is the rest.
+    let regime = normalized.exponent >> es;
+    let exponent = (normalized.exponent & ((1 << es) - 1)) as u128;
+
+    // Numbers beyond the largest regime saturate to maxpos or minpos.
+    let max_regime = n as i64 - 2;
+    let magnitude = if regime > max_regime {
+        ConversionResult::Imprecise((1 << (n - 1)) - 1)
+    } else if regime < -max_regime {
+        ConversionResult::Imprecise(1)
+    } else {
+        // A regime of k >= 0 is k + 1 ones and a zero, and a regime of k < 0
+        // is -k zeros and a one.
+        let (regime_bits, regime_length) = if regime >= 0 {
+            (((1 << (regime + 1)) - 1) << 1, regime + 2)
+        } else {
+            (1, 1 - regime)
+        };
+
+        // 64 fraction bits are always more than enough for rounding, as long
+        // as we remember whether any bits after them were set.
+        let (mantissa, direction) = normalized
+            .round_to_quantum(normalized.exponent - 64, RoundingMode::TowardZero)
+            .expect("a truncated mantissa always fits in a u128");
+        let fraction = mantissa & ((1 << 64) - 1);
+        let pattern = (regime_bits << (es + 64)) | (exponent << 64) | fraction;
+        let pattern_length = regime_length as u32 + es + 64;
+
+        // Keep the bits after the sign bit, and round the rest.
+        let shift = pattern_length - (n - 1);
+        let truncated = pattern >> shift;
+        let round = (pattern >> (shift - 1)) & 1 == 1;
+        let sticky =
+            pattern & ((1 << (shift - 1)) - 1) != 0 || direction != RoundingDirection::Exact;
+        if !round && !sticky {
+            ConversionResult::Precise(truncated)
+        } else if round && (sticky || truncated & 1 == 1) {
+            ConversionResult::Imprecise(truncated + 1)
+        } else {
+            ConversionResult::Imprecise(truncated)
+        }
+    };
+
+    // Negative numbers are the two's complement of the positive ones.
+    let mask = (1 << n) - 1;
+    if literal.is_positive {
+        magnitude
+    } else {
+        magnitude.map(|bits| bits.wrapping_neg() & mask)
+    }
+}
+
+macro_rules! posit_type {
+    ($name:ident, $bits_type:ty, $n:expr) => {
+        impl $name {
+            /// NaR, "not a real", which is the only posit that is not a number.
+            pub const NAR: $name = $name(1 << ($n - 1));
+
+            /// Create a number from its bits.
+            pub const fn from_bits(bits: $bits_type) -> $name {
+                $name(bits)
+            }
+
+            /// Get the bits of the number.
+            pub const fn to_bits(self) -> $bits_type {
+                self.0
+            }
+
+            /// Convert a literal to a posit with `es` exponent bits. Numbers
+            /// are rounded to nearest, ties to even, except that they never
+            /// round to zero or NaR, and saturate to the largest and smallest
+            /// posits instead.
+            ///
+            /// This is the only rounding the posit standard defines, so unlike
+            /// the other formats there is no `from_literal_rounded`, and
+            /// `Imprecise` is the only flag.
+            pub fn from_literal(literal: FloatLiteral, es: PositEs) -> ConversionResult<$name> {
+                to_posit_bits(&literal, $n, es).map(|bits| $name(bits as $bits_type))
+            }
+        }
+    };
+}
+
+/// An 8-bit posit, stored as its bits.
+///
+/// ```rust
+/// use hexponent::{FloatLiteral, Posit8, PositEs};
+/// let literal: FloatLiteral = "0x3".parse().unwrap();
+/// assert_eq!(Posit8::from_literal(literal, PositEs::Es0).inner().to_bits(), 0x68);
+/// let literal: FloatLiteral = "-0x1p100".parse().unwrap();
+/// assert_eq!(Posit8::from_literal(literal, PositEs::Es0).inner().to_bits(), 0x81);
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Posit8(u8);
+
+/// A 16-bit posit, stored as its bits.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Posit16(u16);
+
+/// A 32-bit posit, stored as its bits.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Posit32(u32);
+
+posit_type!(Posit8, u8, 8);
+posit_type!(Posit16, u16, 16);
+posit_type!(Posit32, u32, 32);
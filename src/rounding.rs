@@ -0,0 +1,141 @@
+use crate::FloatLiteral;
+
+/// Rounding modes for conversions, from the IEEE 754 specification.
+///
+/// The default rounding mode is `NearestEven`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RoundingMode {
+    /// Round to the nearest value, with ties going to the value with an even
+    /// least significant digit.
+    NearestEven,
+    /// Round to the nearest value, with ties going away from zero.
+    NearestAway,
+    /// Round toward zero, also known as truncation.
+    TowardZero,
+    /// Round toward positive infinity.
+    TowardPositive,
+    /// Round toward negative infinity.
+    TowardNegative,
+}
+
+impl Default for RoundingMode {
+    fn default() -> RoundingMode {
+        RoundingMode::NearestEven
+    }
+}
+
+impl RoundingMode {
+    /// Return whether a value should be rounded up in magnitude.
+    ///
+    /// `odd` is whether the truncated value is odd, `round` is the first bit
+    /// that was cut off and `sticky` is whether any bits after that are set.
+    pub(crate) fn rounds_up(self, is_positive: bool, odd: bool, round: bool, sticky: bool) -> bool {
+        match self {
+            RoundingMode::NearestEven => round && (sticky || odd),
+            RoundingMode::NearestAway => round,
+            RoundingMode::TowardZero => false,
+            RoundingMode::TowardPositive => is_positive && (round || sticky),
+            RoundingMode::TowardNegative => !is_positive && (round || sticky),
+        }
+    }
+
+    /// Return whether a value too large for a format should become infinity,
+    /// rather than the largest finite value.
+    pub(crate) fn overflows_to_infinity(self, is_positive: bool) -> bool {
+        match self {
+            RoundingMode::NearestEven | RoundingMode::NearestAway => true,
+            RoundingMode::TowardZero => false,
+            RoundingMode::TowardPositive => is_positive,
+            RoundingMode::TowardNegative => !is_positive,
+        }
+    }
+}
+
+/// The direction a conversion rounded in, in terms of magnitude.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RoundingDirection {
+    /// The result is exactly the literal.
+    Exact,
+    /// The result is larger in magnitude than the literal.
+    Up,
+    /// The result is smaller in magnitude than the literal.
+    Down,
+}
+
+/// The leading bits of a non-zero literal.
+pub(crate) struct Normalized {
+    is_positive: bool,
+    /// The leading 128 bits of the mantissa, shifted so that the top bit is set.
+    bits: u128,
+    /// Whether any set bits were cut off below `bits`.
+    sticky: bool,
+    /// The binary exponent of the top bit of `bits`.
+    pub(crate) exponent: i64,
+}
+
+impl Normalized {
+    /// Get the leading bits of `literal`, or `None` if the literal is zero.
+    pub(crate) fn new(literal: &FloatLiteral) -> Option<Normalized> {
+        let first_digit = literal.digits.iter().position(|&d| d != 0)?;
+        let digits = &literal.digits[first_digit..];
+
+        let mut bits: u128 = 0;
+        for index in 0..32 {
+            bits = (bits << 4) | digits.get(index).copied().unwrap_or(0) as u128;
+        }
+
+        // There can be at most 3 leading zeros because the first digit is not
+        // zero. Those bits are filled in from the next digit.
+        let leading_zeros = bits.leading_zeros();
+        let next_digit = digits.get(32).copied().unwrap_or(0);
+        bits = (bits << leading_zeros) | (next_digit >> (4 - leading_zeros)) as u128;
+        let sticky = next_digit & ((1 << (4 - leading_zeros)) - 1) != 0
+            || digits.iter().skip(33).any(|&d| d != 0);
+
+        // 4 bits for each hexadecimal offset
+        let exponent = (literal.decimal_offset as i64 - first_digit as i64) * 4
+            + literal.exponent as i64
+            - 1
+            - leading_zeros as i64;
+
+        Some(Normalized {
+            is_positive: literal.is_positive,
+            bits,
+            sticky,
+            exponent,
+        })
+    }
+
+    /// Round the magnitude to an integer multiple of `2^quantum`. Returns the
+    /// multiple and the direction it was rounded in, or `None` if the multiple
+    /// does not fit in a `u128`.
+    pub(crate) fn round_to_quantum(
+        &self,
+        quantum: i64,
+        mode: RoundingMode,
+    ) -> Option<(u128, RoundingDirection)> {
+        // The bottom bit of `bits` has an exponent of `exponent - 127`.
+        let shift = quantum - (self.exponent - 127);
+        let (multiple, round, sticky) = if shift < 0 {
+            return None;
+        } else if shift == 0 {
+            (self.bits, false, self.sticky)
+        } else if shift <= 128 {
+            let shift = shift as u32;
+            let multiple = self.bits.checked_shr(shift).unwrap_or(0);
+            let round = (self.bits >> (shift - 1)) & 1 == 1;
+            let sticky = self.bits & ((1 << (shift - 1)) - 1) != 0 || self.sticky;
+            (multiple, round, sticky)
+        } else {
+            (0, false, true)
+        };
+
+        if !round && !sticky {
+            Some((multiple, RoundingDirection::Exact))
+        } else if mode.rounds_up(self.is_positive, multiple & 1 == 1, round, sticky) {
+            Some((multiple.checked_add(1)?, RoundingDirection::Up))
+        } else {
+            Some((multiple, RoundingDirection::Down))
+        }
+    }
+}
@@ -1,4 +1,15 @@
-use crate::{parse_hex_float, ConversionResult, FloatLiteral, ParseError, ParseErrorKind};
+#[cfg(not(feature = "std"))]
+use alloc::{format, string::ToString, vec, vec::Vec};
+
+use crate::exact::Dyadic;
+use crate::{
+    format_f32_exact, format_f64_exact, parse_hex_float, BlockSizeError, Conversion,
+    ConversionFlags, ConversionResult, ExactFraction, ExactValue, F8E4M3Fnuz, F8E5M2Fnuz, FPFormat,
+    FloatLiteral, FormatDescriptor, HexFormatOptions, HfpExtended, HfpLong, HfpShort, IntegerError,
+    MxBlock, MxElement, OverflowError, ParseError, ParseErrorKind, Posit16, Posit32, Posit8,
+    PositEs, RoundingDirection, RoundingMode, SubnormalStyle, VaxD, VaxF, VaxG, BF16, F128, F16,
+    F80, F8E4M3, F8E5M2,
+};
 
 // This macros serves two functions:
 // 1. It avoids the float_cmp clippy lint
@@ -40,6 +51,47 @@ right: `{:?}` (`{:016x}`)"#,
     };
 }
 
+fn literal(s: &str) -> FloatLiteral {
+    s.parse().unwrap()
+}
+
+/// Convert a literal to the bits of `F`, rounding to nearest, ties to even.
+fn bits<F: FPFormat>(s: &str) -> ConversionResult<u128> {
+    crate::fpformat::layout::<F>()
+        .convert_bits(&literal(s), RoundingMode::NearestEven)
+        .result()
+}
+
+/// Get an exact literal for a finite `f64`.
+fn f64_literal(f: f64) -> FloatLiteral {
+    let bits = f.to_bits();
+    let sign = if bits >> 63 == 1 { "-" } else { "" };
+    let exponent = ((bits >> 52) & 0x7ff) as i32;
+    let mantissa = bits & ((1 << 52) - 1);
+    let s = if exponent == 0 {
+        format!("{}0x{:x}p-1074", sign, mantissa)
+    } else {
+        format!("{}0x{:x}p{}", sign, mantissa | (1 << 52), exponent - 1075)
+    };
+    s.parse().unwrap()
+}
+
+/// A xorshift generator, for repeatable tests with random values.
+struct XorShift(u64);
+
+impl XorShift {
+    fn new() -> XorShift {
+        XorShift(0x2545_f491_4f6c_dd1d)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
 fn test_float(s: &str, result: f32) {
     let float_repr = s.parse::<FloatLiteral>().unwrap();
     let float_result: f32 = float_repr.convert().inner();
@@ -70,6 +122,30 @@ fn test_both(s: &str, float_result: f32) {
     test_double(s, double_result);
 }
 
+fn test_rounded_float(s: &str, mode: RoundingMode, result: f32) {
+    let float_repr = s.parse::<FloatLiteral>().unwrap();
+    let float_result: f32 = float_repr.convert_rounded(mode).inner();
+    assert_eq_float!(float_result, result);
+}
+
+fn test_rounded_double(s: &str, mode: RoundingMode, result: f64) {
+    let float_repr = s.parse::<FloatLiteral>().unwrap();
+    let double_result: f64 = float_repr.convert_rounded(mode).inner();
+    assert_eq_double!(double_result, result);
+}
+
+/// Assert that a literal is exactly equal to the value of a string.
+fn assert_exact(literal: &FloatLiteral, s: &str) {
+    let expected = s.parse::<FloatLiteral>().unwrap();
+    let difference = Dyadic::from_literal(literal).sub(&Dyadic::from_literal(&expected));
+    assert!(
+        difference.mantissa.is_zero() && literal.is_positive == expected.is_positive,
+        "{:?} is not exactly {:?}",
+        literal,
+        s
+    );
+}
+
 fn test_parse_error_kind(s: &str, error: ParseErrorKind) {
     assert_eq!(s.parse::<FloatLiteral>().unwrap_err().kind, error);
 }
@@ -149,11 +225,35 @@ fn test_overflow_underflow() {
 }
 
 #[test]
-#[ignore]
 fn test_subnormal() {
-    // I haven't implemented subnormal numbers yet.
-    test_float("0x1p-128", 0.0);
-    test_float("-0x1p-128", -0.0);
+    test_float("0x1p-128", f32::from_bits(0x0020_0000));
+    test_float("-0x1p-128", -f32::from_bits(0x0020_0000));
+    test_float("0x1p-149", f32::from_bits(1));
+    test_float("0x1.fffffcp-127", f32::from_bits(0x007f_ffff));
+    test_double("0x1p-1074", f64::from_bits(1));
+    test_double(
+        "0x0.fffffffffffffp-1022",
+        f64::from_bits(0x000f_ffff_ffff_ffff),
+    );
+
+    // Rounding at the bottom of the subnormal range.
+    test_float("0x1p-150", 0.0);
+    test_float("-0x1p-150", -0.0);
+    test_float("0x1.8p-149", f32::from_bits(2));
+    test_float("0x1.4p-149", f32::from_bits(1));
+    test_double("0x1p-1075", 0.0);
+    test_double("0x1.8p-1075", f64::from_bits(1));
+
+    // Older versions of glibc round these down to zero (glibc bug 30220), so
+    // they are not checked against libc.
+    let literal = "0x1.000001p-150".parse::<FloatLiteral>().unwrap();
+    assert_eq_float!(literal.convert().inner(), f32::from_bits(1));
+    let literal = "0x1.0000000000001p-1075".parse::<FloatLiteral>().unwrap();
+    assert_eq_double!(literal.convert().inner(), f64::from_bits(1));
+
+    // Rounding up into the smallest normal number.
+    test_float("0x1.fffffep-127", f32::MIN_POSITIVE);
+    test_double("0x0.fffffffffffff8p-1022", f64::MIN_POSITIVE);
 }
 
 #[test]
@@ -201,10 +301,8 @@ fn test_zero_trimming() {
     test_both("0x0.0000000001p+40", 1.0);
     test_both("0x10000000000p-40", 1.0);
 
-    // Right now these can only be tested to not crash because my rounding is
-    // incorrect.
-    "0x10000000000".parse::<FloatLiteral>().unwrap();
-    "0x.0000000001".parse::<FloatLiteral>().unwrap();
+    test_double("0x10000000000", 1_099_511_627_776.0);
+    test_double("0x.0000000001", 9.094_947_017_729_282e-13);
 }
 
 #[test]
@@ -214,9 +312,1696 @@ fn test_double_precision() {
     test_double("0x1000000001", 68_719_476_737.0);
 }
 
+#[test]
+fn test_rounding() {
+    // Exactly representable with 24 bits.
+    test_float("0x1.fffffep0", 1.999_999_9);
+
+    // Halfway cases round to even.
+    test_float("0x1.000001p0", 1.0);
+    test_float("0x1.000003p0", 1.000_000_2);
+    test_double("0x1.00000000000008p0", 1.0);
+    test_double("0x1.00000000000018p0", 1.000_000_000_000_000_4);
+
+    // Anything above halfway rounds up, no matter how far down it is.
+    test_float(
+        "0x1.0000010000000000000000000000000000000001p0",
+        1.000_000_1,
+    );
+    test_double(
+        "0x1.000000000000080000000000000000000000000001p0",
+        1.000_000_000_000_000_2,
+    );
+    test_float("0x1.00000100000000000000000000000000000001p0", 1.000_000_1);
+
+    // Rounding up can carry into the exponent.
+    test_float("0x1.ffffffp0", 2.0);
+    test_float("0x0.ffffffffffp0", 1.0);
+    test_double("0x1.fffffffffffff8p0", 2.0);
+
+    // Rounding up can overflow to infinity.
+    test_float("0x1.ffffffp127", f32::INFINITY);
+    test_float("-0x1.ffffffp127", f32::NEG_INFINITY);
+    test_float("0x1.fffffefp127", f32::MAX);
+    test_double("0x1.fffffffffffff8p1023", f64::INFINITY);
+    test_double("0x1.fffffffffffff7ffp1023", f64::MAX);
+}
+
+#[test]
+fn test_rounding_modes() {
+    use RoundingMode::*;
+    let one_up = 1.0 + f32::EPSILON;
+    let two_up = 1.0 + 2.0 * f32::EPSILON;
+
+    // Ties
+    test_rounded_float("0x1.000001p0", NearestEven, 1.0);
+    test_rounded_float("0x1.000001p0", NearestAway, one_up);
+    test_rounded_float("0x1.000001p0", TowardZero, 1.0);
+    test_rounded_float("0x1.000001p0", TowardPositive, one_up);
+    test_rounded_float("0x1.000001p0", TowardNegative, 1.0);
+    test_rounded_float("-0x1.000001p0", NearestEven, -1.0);
+    test_rounded_float("-0x1.000001p0", NearestAway, -one_up);
+    test_rounded_float("-0x1.000001p0", TowardZero, -1.0);
+    test_rounded_float("-0x1.000001p0", TowardPositive, -1.0);
+    test_rounded_float("-0x1.000001p0", TowardNegative, -one_up);
+    test_rounded_float("0x1.000003p0", NearestEven, two_up);
+    test_rounded_float("0x1.000003p0", NearestAway, two_up);
+    test_rounded_float("0x1.000003p0", TowardZero, one_up);
+
+    // Below halfway
+    test_rounded_float("0x1.0000008p0", NearestAway, 1.0);
+    test_rounded_float("0x1.0000008p0", TowardPositive, one_up);
+    test_rounded_float("-0x1.0000008p0", TowardPositive, -1.0);
+    test_rounded_double("0x1.00000000000001p0", TowardZero, 1.0);
+    test_rounded_double("0x1.00000000000001p0", TowardPositive, 1.0 + f64::EPSILON);
+
+    // Exact values are never rounded.
+    for &mode in &[
+        NearestEven,
+        NearestAway,
+        TowardZero,
+        TowardPositive,
+        TowardNegative,
+    ] {
+        let literal = "-0x1.8p0".parse::<FloatLiteral>().unwrap();
+        assert_eq!(
+            literal.convert_rounded::<f32>(mode),
+            ConversionResult::Precise(-1.5)
+        );
+    }
+
+    // Overflow
+    test_rounded_float("0x1p128", NearestEven, f32::INFINITY);
+    test_rounded_float("0x1p128", NearestAway, f32::INFINITY);
+    test_rounded_float("0x1p128", TowardZero, f32::MAX);
+    test_rounded_float("0x1p128", TowardPositive, f32::INFINITY);
+    test_rounded_float("0x1p128", TowardNegative, f32::MAX);
+    test_rounded_float("-0x1p128", TowardZero, f32::MIN);
+    test_rounded_float("-0x1p128", TowardPositive, f32::MIN);
+    test_rounded_float("-0x1p128", TowardNegative, f32::NEG_INFINITY);
+    test_rounded_float("0x1.ffffffp127", TowardZero, f32::MAX);
+    test_rounded_double("0x1p1024", TowardZero, f64::MAX);
+
+    // Underflow
+    let min_subnormal = f32::from_bits(1);
+    test_rounded_float("0x1p-200", NearestAway, 0.0);
+    test_rounded_float("0x1p-200", TowardZero, 0.0);
+    test_rounded_float("0x1p-200", TowardPositive, min_subnormal);
+    test_rounded_float("0x1p-200", TowardNegative, 0.0);
+    test_rounded_float("-0x1p-200", TowardPositive, -0.0);
+    test_rounded_float("-0x1p-200", TowardNegative, -min_subnormal);
+    test_rounded_float("0x1p-150", NearestAway, min_subnormal);
+    test_rounded_double("0x1p-2000", TowardPositive, f64::from_bits(1));
+}
+
+#[test]
+fn test_conversion_flags() {
+    fn convert(s: &str, mode: RoundingMode) -> Conversion<f32> {
+        s.parse::<FloatLiteral>().unwrap().convert_with_flags(mode)
+    }
+    let none = ConversionFlags::default();
+    let inexact = ConversionFlags {
+        inexact: true,
+        ..none
+    };
+    let overflow = ConversionFlags {
+        inexact: true,
+        overflow: true,
+        ..none
+    };
+    let tiny = ConversionFlags {
+        tiny_before_rounding: true,
+        tiny_after_rounding: true,
+        ..none
+    };
+    let underflow = ConversionFlags {
+        inexact: true,
+        underflow: true,
+        ..tiny
+    };
+
+    let conversion = convert("0x1p0", RoundingMode::NearestEven);
+    assert_eq!(conversion.flags, none);
+    assert_eq!(conversion.direction, RoundingDirection::Exact);
+    assert_eq!(convert("0x0", RoundingMode::NearestEven).flags, none);
+
+    let conversion = convert("-0x1.000001p0", RoundingMode::NearestEven);
+    assert_eq!(conversion.flags, inexact);
+    assert_eq!(conversion.direction, RoundingDirection::Down);
+    let conversion = convert("-0x1.000001p0", RoundingMode::TowardNegative);
+    assert_eq!(conversion.flags, inexact);
+    assert_eq!(conversion.direction, RoundingDirection::Up);
+
+    let conversion = convert("0x1p128", RoundingMode::NearestEven);
+    assert_eq!(conversion.flags, overflow);
+    assert_eq!(conversion.direction, RoundingDirection::Up);
+    let conversion = convert("0x1p128", RoundingMode::TowardZero);
+    assert_eq!(conversion.flags, overflow);
+    assert_eq!(conversion.direction, RoundingDirection::Down);
+    assert_eq!(
+        convert("0x1.fffffep127", RoundingMode::NearestEven).flags,
+        none
+    );
+
+    // Exact subnormals are tiny, but don't underflow.
+    assert_eq!(convert("0x1p-149", RoundingMode::NearestEven).flags, tiny);
+
+    let conversion = convert("0x1p-150", RoundingMode::NearestEven);
+    assert_eq!(conversion.flags, underflow);
+    assert_eq!(conversion.direction, RoundingDirection::Down);
+    assert_eq!(conversion.value, 0.0);
+
+    // Rounds up to the smallest normal number even with unlimited exponent
+    // range, so it is only tiny before rounding.
+    let conversion = convert("0x1.ffffffp-127", RoundingMode::NearestEven);
+    assert_eq!(
+        conversion.flags,
+        ConversionFlags {
+            inexact: true,
+            tiny_before_rounding: true,
+            ..none
+        }
+    );
+    assert_eq!(conversion.direction, RoundingDirection::Up);
+
+    // Would stay tiny with unlimited exponent range, but loses a bit to the
+    // subnormal range.
+    let conversion = convert("0x1.fffffep-127", RoundingMode::NearestEven);
+    assert_eq!(conversion.flags, underflow);
+    assert_eq!(conversion.value, f32::MIN_POSITIVE);
+    let conversion = convert("0x1.fffffep-127", RoundingMode::TowardZero);
+    assert_eq!(conversion.flags, underflow);
+    assert_eq!(conversion.direction, RoundingDirection::Down);
+
+    assert_eq!(
+        convert("0x1.000001p0", RoundingMode::NearestEven).result(),
+        ConversionResult::Imprecise(1.0)
+    );
+}
+
+#[test]
+fn test_rounding_error() {
+    use RoundingMode::*;
+    fn error<F: crate::FPFormat>(s: &str, mode: RoundingMode) -> Option<crate::RoundingError> {
+        s.parse::<FloatLiteral>().unwrap().rounding_error::<F>(mode)
+    }
+
+    let e = error::<f32>("0x1.000001p0", NearestEven).unwrap();
+    assert_exact(e.difference.as_ref().unwrap(), "-0x1p-24");
+    assert_exact(e.ulps.as_ref().unwrap(), "-0x0.8");
+    assert!(e.relative_bound >= 0.9999999 / 16_777_216.0);
+    assert!(e.relative_bound <= 1.0000001 / 16_777_216.0);
+
+    let e = error::<f32>("0x1.000001p0", TowardPositive).unwrap();
+    assert_exact(e.difference.as_ref().unwrap(), "0x1p-24");
+    assert_exact(e.ulps.as_ref().unwrap(), "0x0.8");
+
+    let e = error::<f32>("-0x1.000001p0", NearestEven).unwrap();
+    assert_exact(e.difference.as_ref().unwrap(), "0x1p-24");
+
+    // Far away digits are kept exactly.
+    let e = error::<f32>(
+        "0x1.0000008000000000000000000000000000000001p0",
+        NearestEven,
+    )
+    .unwrap();
+    assert_exact(
+        e.difference.as_ref().unwrap(),
+        "-0x.0000008000000000000000000000000000000001p0",
+    );
+    assert_exact(
+        e.ulps.as_ref().unwrap(),
+        "-0x1.0000000000000000000000000000000002p-2",
+    );
+
+    // Exact conversions have no error.
+    let e = error::<f64>("0x1.000001p0", NearestEven).unwrap();
+    assert_exact(e.difference.as_ref().unwrap(), "0x0");
+    assert_exact(e.ulps.as_ref().unwrap(), "0x0");
+    assert_eq!(e.relative_bound, 0.0);
+    let e = error::<f32>("-0x0", NearestEven).unwrap();
+    assert_exact(e.difference.as_ref().unwrap(), "0x0");
+    assert_eq!(e.relative_bound, 0.0);
+
+    // Underflow to zero
+    let e = error::<f32>("0x1p-150", NearestEven).unwrap();
+    assert_exact(e.difference.as_ref().unwrap(), "-0x1p-150");
+    assert_exact(e.ulps.as_ref().unwrap(), "-0x0.8");
+    assert!(e.relative_bound >= 1.0 && e.relative_bound < 1.000001);
+    let e = error::<f64>("0x1p-2000", NearestEven).unwrap();
+    assert_exact(e.difference.as_ref().unwrap(), "-0x1p-2000");
+
+    // Overflow
+    assert!(error::<f32>("0x1p128", NearestEven).is_none());
+    let e = error::<f32>("0x1p128", TowardZero).unwrap();
+    assert_exact(e.difference.as_ref().unwrap(), "-0x1p104");
+    assert_exact(e.ulps.as_ref().unwrap(), "-0x1");
+
+    // Far beyond the range of the format, the difference isn't exact.
+    let e = error::<f32>("0x1p2000000000", TowardZero).unwrap();
+    assert!(e.difference.is_none() && e.ulps.is_none());
+    assert_eq!(e.relative_bound, 1.0);
+    let e = error::<f32>("0x1p-2000000000", TowardPositive).unwrap();
+    assert!(e.difference.is_none());
+    assert_eq!(e.relative_bound, f64::INFINITY);
+    // Close enough for an exact difference, but the relative error still
+    // overflows an f64.
+    let e = error::<f32>("0x1p-1200", TowardPositive).unwrap();
+    assert!(e.difference.is_some());
+    assert_eq!(e.relative_bound, core::f64::INFINITY);
+    let e = error::<f32>("0x1p-60000", TowardPositive).unwrap();
+    assert_eq!(e.relative_bound, core::f64::INFINITY);
+    let e = error::<f32>("0x1p-2000000000", TowardZero).unwrap();
+    assert_exact(e.difference.as_ref().unwrap(), "-0x1p-2000000000");
+}
+
+#[test]
+fn test_half_precision() {
+    use ConversionResult::{Imprecise, Precise};
+
+    assert_eq!(bits::<F16>("0x0"), Precise(0x0000));
+    assert_eq!(bits::<F16>("-0x0"), Precise(0x8000));
+    assert_eq!(bits::<F16>("0x1"), Precise(0x3c00));
+    assert_eq!(bits::<F16>("-0x2"), Precise(0xc000));
+    assert_eq!(bits::<F16>("0x1.ffcp15"), Precise(0x7bff));
+    assert_eq!(bits::<F16>("0x1p-14"), Precise(0x0400));
+
+    // Rounding
+    assert_eq!(bits::<F16>("0x1.002p0"), Imprecise(0x3c00));
+    assert_eq!(bits::<F16>("0x1.006p0"), Imprecise(0x3c02));
+    assert_eq!(bits::<F16>("0x1.0020000001p0"), Imprecise(0x3c01));
+    assert_eq!(bits::<F16>("0x1.00cp0"), Precise(0x3c03));
+    assert_eq!(bits::<F16>("0x1.ffep0"), Imprecise(0x4000));
+
+    // Overflow
+    assert_eq!(bits::<F16>("0x1.ffdp15"), Imprecise(0x7bff));
+    assert_eq!(bits::<F16>("0x1.ffep15"), Imprecise(0x7c00));
+    assert_eq!(bits::<F16>("-0x1p16"), Imprecise(0xfc00));
+
+    // Subnormals
+    assert_eq!(bits::<F16>("0x1p-24"), Precise(0x0001));
+    assert_eq!(bits::<F16>("0x1.ff8p-15"), Precise(0x03ff));
+    assert_eq!(bits::<F16>("0x1p-25"), Imprecise(0x0000));
+    assert_eq!(bits::<F16>("-0x1.8p-24"), Imprecise(0x8002));
+    assert_eq!(bits::<F16>("0x1.ffcp-15"), Imprecise(0x0400));
+
+    // Conversion to f32 is exact.
+    assert_eq_float!(F16::from_bits(0x0001).to_f32(), 5.960_464_5e-8);
+    assert_eq_float!(F16::from_bits(0x83ff).to_f32(), -6.097_555e-5);
+    assert_eq_float!(F16::from_bits(0x3555).to_f32(), 0.333_251_95);
+    assert_eq_float!(F16::from_bits(0x7bff).to_f32(), 65504.0);
+    assert_eq_float!(F16::from_bits(0xfc00).to_f32(), f32::NEG_INFINITY);
+    assert!(F16::from_bits(0x7e00).to_f32().is_nan());
+    assert_eq!((-F16::from_bits(0x3c00)).to_bits(), 0xbc00);
+}
+
+#[test]
+fn test_bfloat16() {
+    use ConversionResult::{Imprecise, Precise};
+
+    assert_eq!(bits::<BF16>("0x0"), Precise(0x0000));
+    assert_eq!(bits::<BF16>("-0x0"), Precise(0x8000));
+    assert_eq!(bits::<BF16>("0x1"), Precise(0x3f80));
+    assert_eq!(bits::<BF16>("-0x1.fep127"), Precise(0xff7f));
+    assert_eq!(bits::<BF16>("0x1p-126"), Precise(0x0080));
+
+    // Rounding is done once, straight from the literal. Rounding to f32 first
+    // would round this down to a tie, and then to even.
+    assert_eq!(bits::<BF16>("0x1.01000001p0"), Imprecise(0x3f81));
+    assert_eq!(bits::<BF16>("0x1.01p0"), Imprecise(0x3f80));
+    assert_eq!(bits::<BF16>("0x1.03p0"), Imprecise(0x3f82));
+    assert_eq!(bits::<BF16>("0x1.ffp0"), Imprecise(0x4000));
+
+    // Overflow
+    assert_eq!(bits::<BF16>("0x1.feffffp127"), Imprecise(0x7f7f));
+    assert_eq!(bits::<BF16>("0x1.ffp127"), Imprecise(0x7f80));
+    assert_eq!(bits::<BF16>("-0x1p1000"), Imprecise(0xff80));
+
+    // Subnormals
+    assert_eq!(bits::<BF16>("0x1p-133"), Precise(0x0001));
+    assert_eq!(bits::<BF16>("0x1p-134"), Imprecise(0x0000));
+    assert_eq!(bits::<BF16>("0x1.8p-133"), Imprecise(0x0002));
+    assert_eq!(bits::<BF16>("0x1.fcp-127"), Precise(0x007f));
+    assert_eq!(bits::<BF16>("0x1.fep-127"), Imprecise(0x0080));
+
+    assert_eq_float!(BF16::from_bits(0x3f81).to_f32(), 1.007_812_5);
+    assert_eq_float!(
+        f32::from(BF16::from_bits(0x8001)),
+        -f32::from_bits(0x0001_0000)
+    );
+    assert_eq!((-BF16::from_bits(0x3f80)).to_bits(), 0xbf80);
+}
+
+#[test]
+fn test_extended_precision() {
+    use ConversionResult::{Imprecise, Precise};
+
+    // Expected values are from glibc's strtold.
+    assert_eq!(bits::<F80>("0x0"), Precise(0x0000_0000_0000_0000_0000));
+    assert_eq!(bits::<F80>("-0x0"), Precise(0x8000_0000_0000_0000_0000));
+    assert_eq!(bits::<F80>("0x1"), Precise(0x3fff_8000_0000_0000_0000));
+    assert_eq!(bits::<F80>("-0x2"), Precise(0xc000_8000_0000_0000_0000));
+    assert_eq!(
+        bits::<F80>("0x1.921fb54442d1846ap+1"),
+        Precise(0x4000_c90f_daa2_2168_c235)
+    );
+
+    // Rounding
+    assert_eq!(
+        bits::<F80>("0x1.0000000000000001p0"),
+        Imprecise(0x3fff_8000_0000_0000_0000)
+    );
+    assert_eq!(
+        bits::<F80>("-0x1.0000000000000003p0"),
+        Imprecise(0xbfff_8000_0000_0000_0002)
+    );
+    assert_eq!(
+        bits::<F80>("0x1.00000000000000010001p0"),
+        Imprecise(0x3fff_8000_0000_0000_0001)
+    );
+
+    // Overflow
+    assert_eq!(
+        bits::<F80>("0x1.fffffffffffffffep16383"),
+        Precise(0x7ffe_ffff_ffff_ffff_ffff)
+    );
+    assert_eq!(
+        bits::<F80>("0x1.ffffffffffffffffp16383"),
+        Imprecise(0x7fff_8000_0000_0000_0000)
+    );
+
+    // Subnormals don't have the integer bit set, until they round up to the
+    // smallest normal number.
+    assert_eq!(
+        bits::<F80>("0x1p-16382"),
+        Precise(0x0001_8000_0000_0000_0000)
+    );
+    assert_eq!(
+        bits::<F80>("0x1p-16400"),
+        Precise(0x0000_0000_2000_0000_0000)
+    );
+    assert_eq!(
+        bits::<F80>("0x1p-16445"),
+        Precise(0x0000_0000_0000_0000_0001)
+    );
+    assert_eq!(
+        bits::<F80>("0x1p-16446"),
+        Imprecise(0x0000_0000_0000_0000_0000)
+    );
+    assert_eq!(
+        bits::<F80>("0x1.8p-16445"),
+        Imprecise(0x0000_0000_0000_0000_0002)
+    );
+    assert_eq!(
+        bits::<F80>("0x1.fffffffffffffffcp-16383"),
+        Precise(0x0000_7fff_ffff_ffff_ffff)
+    );
+    assert_eq!(
+        bits::<F80>("0x1.fffffffffffffffep-16383"),
+        Imprecise(0x0001_8000_0000_0000_0000)
+    );
+
+    let one = F80::from_le_bytes([0, 0, 0, 0, 0, 0, 0, 0x80, 0xff, 0x3f]);
+    assert_eq!(one.to_bits(), 0x3fff_8000_0000_0000_0000);
+    assert_eq!(
+        (-one).to_le_bytes(),
+        [0, 0, 0, 0, 0, 0, 0, 0x80, 0xff, 0xbf]
+    );
+}
+
+#[test]
+fn test_quad_precision() {
+    use ConversionResult::{Imprecise, Precise};
+
+    // Expected values are from libquadmath's strtoflt128.
+    assert_eq!(bits::<F128>("0x0"), Precise(0));
+    assert_eq!(bits::<F128>("-0x0"), Precise(1 << 127));
+    assert_eq!(
+        bits::<F128>("0x1"),
+        Precise(0x3fff_0000_0000_0000_0000_0000_0000_0000)
+    );
+    assert_eq!(
+        bits::<F128>("-0x1.921fb54442d18469898cc51701b839a2p+1"),
+        Imprecise(0xc000_921f_b544_42d1_8469_898c_c517_01b8)
+    );
+
+    // Rounding
+    assert_eq!(
+        bits::<F128>("0x1.00000000000000000000000000008p0"),
+        Imprecise(0x3fff_0000_0000_0000_0000_0000_0000_0000)
+    );
+    assert_eq!(
+        bits::<F128>("0x1.00000000000000000000000000018p0"),
+        Imprecise(0x3fff_0000_0000_0000_0000_0000_0000_0002)
+    );
+    assert_eq!(
+        bits::<F128>("0x1.0000000000000000000000000000800000001p0"),
+        Imprecise(0x3fff_0000_0000_0000_0000_0000_0000_0001)
+    );
+
+    // Overflow
+    assert_eq!(
+        bits::<F128>("0x1.ffffffffffffffffffffffffffffp16383"),
+        Precise(0x7ffe_ffff_ffff_ffff_ffff_ffff_ffff_ffff)
+    );
+    assert_eq!(
+        bits::<F128>("0x1.ffffffffffffffffffffffffffff8p16383"),
+        Imprecise(0x7fff_0000_0000_0000_0000_0000_0000_0000)
+    );
+
+    // Subnormals
+    assert_eq!(
+        bits::<F128>("0x1p-16382"),
+        Precise(0x0001_0000_0000_0000_0000_0000_0000_0000)
+    );
+    assert_eq!(bits::<F128>("0x1p-16494"), Precise(1));
+    assert_eq!(bits::<F128>("0x1.8p-16494"), Imprecise(2));
+    // Halfway to the smallest subnormal, so the tie rounds to even (zero).
+    assert_eq!(bits::<F128>("0x1p-16495"), Imprecise(0));
+    assert_eq!(
+        bits::<F128>("0x1.ffffffffffffffffffffffffffffp-16383"),
+        Imprecise(0x0001_0000_0000_0000_0000_0000_0000_0000)
+    );
+
+    assert_eq!((-F128::from_bits(0)).to_bits(), 1 << 127);
+}
+
+#[test]
+fn test_fp8() {
+    use ConversionResult::{Imprecise, Precise};
+
+    // E5M2 follows the IEEE rules.
+    assert_eq!(bits::<F8E5M2>("0x1"), Precise(0x3c));
+    assert_eq!(bits::<F8E5M2>("-0x0"), Precise(0x80));
+    assert_eq!(bits::<F8E5M2>("0x1.cp15"), Precise(0x7b));
+    assert_eq!(bits::<F8E5M2>("0x1.ep15"), Imprecise(0x7c));
+    assert_eq!(bits::<F8E5M2>("-0x1p16"), Imprecise(0xfc));
+    assert_eq!(bits::<F8E5M2>("0x1p-16"), Precise(0x01));
+    assert_eq!(bits::<F8E5M2>("0x1p-17"), Imprecise(0x00));
+
+    // E4M3 saturates instead of overflowing to infinity.
+    assert_eq!(bits::<F8E4M3>("0x1"), Precise(0x38));
+    assert_eq!(bits::<F8E4M3>("-0x0"), Precise(0x80));
+    assert_eq!(bits::<F8E4M3>("0x1.cp8"), Precise(0x7e));
+    assert_eq!(bits::<F8E4M3>("0x1.dp8"), Imprecise(0x7e));
+    assert_eq!(bits::<F8E4M3>("0x1.d8p8"), Imprecise(0x7e));
+    assert_eq!(bits::<F8E4M3>("-0x1p1000"), Imprecise(0xfe));
+    assert_eq!(bits::<F8E4M3>("0x1.ep7"), Precise(0x77));
+    assert_eq!(bits::<F8E4M3>("0x1p-9"), Precise(0x01));
+    assert_eq!(bits::<F8E4M3>("-0x1p-11"), Imprecise(0x80));
+
+    // The FNUZ formats have different biases, and no negative zero.
+    assert_eq!(bits::<F8E5M2Fnuz>("0x1"), Precise(0x40));
+    assert_eq!(bits::<F8E5M2Fnuz>("-0x1"), Precise(0xc0));
+    assert_eq!(bits::<F8E5M2Fnuz>("-0x0"), Precise(0x00));
+    assert_eq!(bits::<F8E5M2Fnuz>("0x1.cp15"), Precise(0x7f));
+    assert_eq!(bits::<F8E5M2Fnuz>("0x1p16"), Imprecise(0x7f));
+    assert_eq!(bits::<F8E5M2Fnuz>("0x1p-17"), Precise(0x01));
+    assert_eq!(bits::<F8E5M2Fnuz>("-0x1p-30"), Imprecise(0x00));
+    assert_eq!(bits::<F8E4M3Fnuz>("0x1"), Precise(0x40));
+    assert_eq!(bits::<F8E4M3Fnuz>("-0x0"), Precise(0x00));
+    assert_eq!(bits::<F8E4M3Fnuz>("0x1.ep7"), Precise(0x7f));
+    assert_eq!(bits::<F8E4M3Fnuz>("0x1.fp7"), Imprecise(0x7f));
+    assert_eq!(bits::<F8E4M3Fnuz>("-0x1p8"), Imprecise(0xff));
+    assert_eq!(bits::<F8E4M3Fnuz>("0x1p-10"), Precise(0x01));
+
+    // Saturation is still an overflow, even when rounding toward infinity.
+    let literal = "0x1p9".parse::<FloatLiteral>().unwrap();
+    let conversion = literal.convert_with_flags::<F8E4M3>(RoundingMode::TowardPositive);
+    assert_eq!(conversion.value.to_bits(), 0x7e);
+    assert!(conversion.flags.overflow);
+    assert_eq!(conversion.direction, RoundingDirection::Down);
+
+    assert_eq!((-F8E4M3::from_bits(0x00)).to_bits(), 0x80);
+    assert_eq!(-F8E4M3::NAN, F8E4M3::from_bits(0xff));
+    assert_eq!((-F8E4M3Fnuz::from_bits(0x00)).to_bits(), 0x00);
+    assert_eq!(-F8E4M3Fnuz::NAN, F8E4M3Fnuz::NAN);
+    assert_eq!((-F8E5M2Fnuz::from_bits(0x40)).to_bits(), 0xc0);
+}
+
+#[test]
+fn test_custom_format() {
+    // Formats outside the crate only need to describe their layout.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Half(u16);
+    impl core::ops::Neg for Half {
+        type Output = Half;
+        fn neg(self) -> Half {
+            Half(self.0 ^ 0x8000)
+        }
+    }
+    impl FPFormat for Half {
+        const FORMAT: FormatDescriptor = FormatDescriptor::ieee(5, 10);
+        fn from_bits(bits: u128) -> Half {
+            Half(bits as u16)
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Fnuz(u8);
+    impl core::ops::Neg for Fnuz {
+        type Output = Fnuz;
+        fn neg(self) -> Fnuz {
+            Fnuz((-F8E4M3Fnuz::from_bits(self.0)).to_bits())
+        }
+    }
+    impl FPFormat for Fnuz {
+        const FORMAT: FormatDescriptor = FormatDescriptor {
+            exponent_bias: 8,
+            has_infinity: false,
+            has_signed_zero: false,
+            ..FormatDescriptor::ieee(4, 3)
+        };
+        fn from_bits(bits: u128) -> Fnuz {
+            Fnuz(bits as u8)
+        }
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Flush(u32);
+    impl core::ops::Neg for Flush {
+        type Output = Flush;
+        fn neg(self) -> Flush {
+            Flush(self.0 ^ 0x8000_0000)
+        }
+    }
+    impl FPFormat for Flush {
+        const FORMAT: FormatDescriptor = FormatDescriptor {
+            has_subnormals: false,
+            ..FormatDescriptor::ieee(8, 23)
+        };
+        fn from_bits(bits: u128) -> Flush {
+            Flush(bits as u32)
+        }
+    }
+
+    for s in [
+        "0x1.004p0",
+        "-0x1.ffep15",
+        "0x1p16",
+        "0x1.8p-25",
+        "0x1.8p-127",
+        "-0x0",
+        "0x1.ep7",
+    ]
+    .iter()
+    {
+        let literal: FloatLiteral = s.parse().unwrap();
+        for &mode in [RoundingMode::NearestEven, RoundingMode::TowardNegative].iter() {
+            let half = literal.clone().convert_with_flags::<Half>(mode);
+            let expected = literal.clone().convert_with_flags::<F16>(mode);
+            assert_eq!(half, expected.map(|f| Half(f.to_bits())));
+            let fnuz = literal.clone().convert_with_flags::<Fnuz>(mode);
+            let expected = literal.clone().convert_with_flags::<F8E4M3Fnuz>(mode);
+            assert_eq!(fnuz, expected.map(|f| Fnuz(f.to_bits())));
+            let flush = literal.clone().convert_with_flags::<Flush>(mode);
+            let expected = Flush::FORMAT.convert(&literal, mode);
+            let bytes = flush.map(|f| f.0.to_le_bytes().to_vec());
+            assert_eq!(bytes, expected);
+        }
+    }
+
+    let literal: FloatLiteral = "0x1.0041p0".parse().unwrap();
+    let error = literal
+        .rounding_error::<Half>(RoundingMode::NearestEven)
+        .unwrap();
+    assert_eq!(
+        Dyadic::from_literal(error.ulps.as_ref().unwrap()).exponent,
+        -6
+    );
+}
+
+#[test]
+#[should_panic]
+fn test_custom_format_too_wide() {
+    // The conversions use 128 bit integers, so wider formats need
+    // `FormatDescriptor::convert`.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Binary256;
+    impl core::ops::Neg for Binary256 {
+        type Output = Binary256;
+        fn neg(self) -> Binary256 {
+            self
+        }
+    }
+    impl FPFormat for Binary256 {
+        const FORMAT: FormatDescriptor = FormatDescriptor::ieee(19, 236);
+        fn from_bits(_: u128) -> Binary256 {
+            Binary256
+        }
+    }
+    literal("0x1").convert::<Binary256>();
+}
+
+#[test]
+fn test_format_descriptor() {
+    // Descriptors of the built in formats give the same results.
+    macro_rules! check {
+        ($fp_type:ty, $descriptor:expr) => {
+            let literals = [
+                "0x0",
+                "-0x0",
+                "0x1",
+                "-0x1.8p1",
+                "0x1.fffffffffffffffffffffffffffffp3",
+                "0x1.00000000000000000000000000001p-1",
+                "0x1.5555555555555555555555p-30",
+                "0x1p-125",
+                "0x1.ffp-130",
+                "0x1p-1022",
+                "0x1.8p-1070",
+                "0x1p-16400",
+                "0x1.8p-16460",
+                "0x1.fffp127",
+                "0x1.ffffffffffffffffp1023",
+                "0x1p16383",
+                "0x1p-16500",
+                "0x1.cp8",
+                "0x1.ep15",
+                "0x1.ffp15",
+                "-0x1p-10",
+                "0x1p100000",
+            ];
+            let modes = [
+                RoundingMode::NearestEven,
+                RoundingMode::NearestAway,
+                RoundingMode::TowardZero,
+                RoundingMode::TowardPositive,
+                RoundingMode::TowardNegative,
+            ];
+            for s in literals.iter() {
+                let literal: FloatLiteral = s.parse().unwrap();
+                for &mode in modes.iter() {
+                    let expected = literal.clone().convert_with_flags::<$fp_type>(mode);
+                    let length = $descriptor.total_bits() as usize / 8;
+                    let expected = expected.map(|f| f.to_bits().to_le_bytes()[..length].to_vec());
+                    assert_eq!(
+                        $descriptor.convert(&literal, mode),
+                        expected,
+                        "{} {:?}",
+                        s,
+                        mode
+                    );
+                }
+            }
+        };
+    }
+    check!(f32, FormatDescriptor::ieee(8, 23));
+    check!(f64, FormatDescriptor::ieee(11, 52));
+    check!(F16, FormatDescriptor::ieee(5, 10));
+    check!(BF16, FormatDescriptor::ieee(8, 7));
+    check!(F128, FormatDescriptor::ieee(15, 112));
+    let x87 = FormatDescriptor {
+        explicit_integer_bit: true,
+        ..FormatDescriptor::ieee(15, 63)
+    };
+    check!(F80, x87);
+    let e4m3 = FormatDescriptor {
+        has_infinity: false,
+        ..FormatDescriptor::ieee(4, 3)
+    };
+    check!(F8E4M3, e4m3);
+    let e5m2_fnuz = FormatDescriptor {
+        exponent_bias: 16,
+        has_infinity: false,
+        has_signed_zero: false,
+        ..FormatDescriptor::ieee(5, 2)
+    };
+    check!(F8E5M2Fnuz, e5m2_fnuz);
+
+    // binary256 is wider than any built in format.
+    let binary256 = FormatDescriptor::ieee(19, 236);
+    let convert = |s: &str, mode| {
+        let literal: FloatLiteral = s.parse().unwrap();
+        binary256.convert(&literal, mode)
+    };
+    let mut one = [0; 32];
+    one[29..].copy_from_slice(&[0xf0, 0xff, 0x3f]);
+    let conversion = convert("0x1", RoundingMode::NearestEven);
+    assert_eq!(conversion, Conversion::exact(one.to_vec()));
+    // Halfway between 1 and the next number.
+    let half_ulp = "0x1.000000000000000000000000000000000000000000000000000000000008";
+    let conversion = convert(half_ulp, RoundingMode::NearestEven);
+    assert_eq!(conversion.value, one);
+    assert_eq!(conversion.direction, RoundingDirection::Down);
+    let mut next = one;
+    next[0] = 1;
+    let conversion = convert(half_ulp, RoundingMode::NearestAway);
+    assert_eq!(conversion.value, next);
+    assert_eq!(conversion.direction, RoundingDirection::Up);
+    let conversion = convert("-0x1p-262378", RoundingMode::NearestEven);
+    let mut min_subnormal = [0; 32];
+    min_subnormal[0] = 1;
+    min_subnormal[31] = 0x80;
+    assert_eq!(conversion.value, min_subnormal);
+    assert_eq!(conversion.direction, RoundingDirection::Exact);
+    assert!(conversion.flags.tiny_after_rounding && !conversion.flags.underflow);
+
+    // Without subnormals, tiny numbers round to zero or the smallest normal.
+    let flush = FormatDescriptor {
+        has_subnormals: false,
+        ..FormatDescriptor::ieee(8, 23)
+    };
+    let convert = |s: &str, mode| {
+        let literal: FloatLiteral = s.parse().unwrap();
+        flush.convert(&literal, mode).value
+    };
+    assert_eq!(
+        convert("0x1p-126", RoundingMode::NearestEven),
+        [0, 0, 0x80, 0]
+    );
+    assert_eq!(convert("0x1p-127", RoundingMode::NearestEven), [0, 0, 0, 0]);
+    assert_eq!(
+        convert("0x1.8p-127", RoundingMode::NearestEven),
+        [0, 0, 0x80, 0]
+    );
+    assert_eq!(
+        convert("-0x1.8p-127", RoundingMode::TowardZero),
+        [0, 0, 0, 0x80]
+    );
+    assert_eq!(
+        convert("0x1p-200", RoundingMode::TowardPositive),
+        [0, 0, 0x80, 0]
+    );
+    assert_eq!(
+        convert("0x1.fffffffp-127", RoundingMode::TowardZero),
+        [0, 0, 0, 0]
+    );
+}
+
+#[test]
+fn test_hfp() {
+    macro_rules! hfp {
+        ($hfp_type:ty, $s:expr) => {
+            <$hfp_type>::from_literal($s.parse().unwrap()).map(<$hfp_type>::to_bits)
+        };
+    }
+    use ConversionResult::{Imprecise, Precise};
+
+    assert_eq!(hfp!(HfpShort, "0x1"), Precise(0x4110_0000));
+    assert_eq!(hfp!(HfpShort, "0x.8"), Precise(0x4080_0000));
+    assert_eq!(hfp!(HfpShort, "-0x76.a"), Precise(0xc276_a000));
+    assert_eq!(hfp!(HfpShort, "0x0"), Precise(0x0000_0000));
+    assert_eq!(hfp!(HfpShort, "-0x0"), Precise(0x8000_0000));
+    assert_eq!(hfp!(HfpShort, "0x.1999999999"), Imprecise(0x4019_999a));
+    assert_eq!(
+        hfp!(HfpLong, "0x.1999999999999999"),
+        Imprecise(0x4019_9999_9999_999a)
+    );
+
+    // The precision depends on the leading digit.
+    assert_eq!(hfp!(HfpShort, "0x1.00001"), Precise(0x4110_0001));
+    assert_eq!(hfp!(HfpShort, "0x1.000008"), Imprecise(0x4110_0000));
+    assert_eq!(hfp!(HfpShort, "0x1.000018"), Imprecise(0x4110_0002));
+    assert_eq!(hfp!(HfpShort, "0xf.fffff8"), Imprecise(0x4210_0000));
+    assert_eq!(hfp!(HfpShort, "0x8.000008"), Imprecise(0x4180_0000));
+
+    // Numbers that are too large saturate, and there are no subnormals.
+    assert_eq!(hfp!(HfpShort, "0x1.fffffep251"), Precise(0x7fff_ffff));
+    assert_eq!(hfp!(HfpShort, "-0x1p252"), Imprecise(0xffff_ffff));
+    assert_eq!(hfp!(HfpShort, "0x1p-260"), Precise(0x0010_0000));
+    assert_eq!(hfp!(HfpShort, "0x1p-261"), Imprecise(0x0000_0000));
+    assert_eq!(hfp!(HfpShort, "-0x1.8p-261"), Imprecise(0x8010_0000));
+    assert_eq!(
+        hfp!(HfpLong, "0x1.fffffffffffff8p-261"),
+        Imprecise(0x0010_0000_0000_0000)
+    );
+
+    let literal: FloatLiteral = "0x1p300".parse().unwrap();
+    let conversion = HfpLong::from_literal_with_flags(literal, RoundingMode::TowardZero);
+    assert_eq!(conversion.value.to_bits(), 0x7fff_ffff_ffff_ffff);
+    assert!(conversion.flags.overflow);
+    let literal: FloatLiteral = "0x1p-270".parse().unwrap();
+    let conversion = HfpShort::from_literal_with_flags(literal, RoundingMode::TowardPositive);
+    assert_eq!(conversion.value.to_bits(), 0x0010_0000);
+    assert!(conversion.flags.underflow);
+
+    // The low half of an extended number has a characteristic 14 less than the
+    // high half.
+    assert_eq!(
+        hfp!(HfpExtended, "0x1"),
+        Precise(0x4110_0000_0000_0000_3300_0000_0000_0000)
+    );
+    assert_eq!(
+        hfp!(HfpExtended, "-0x.123456789abcdef0123456789abcd"),
+        Imprecise(0xc012_3456_789a_bcde_b2f0_1234_5678_9abd)
+    );
+    assert_eq!(
+        hfp!(HfpExtended, "0x1p-260"),
+        Precise(0x0010_0000_0000_0000_7200_0000_0000_0000)
+    );
+    assert_eq!(
+        hfp!(HfpExtended, "-0x0"),
+        Precise(0x8000_0000_0000_0000_8000_0000_0000_0000)
+    );
+}
+
+#[test]
+fn test_vax() {
+    macro_rules! vax {
+        ($vax_type:ty, $s:expr) => {
+            <$vax_type>::from_literal($s.parse().unwrap())
+                .map(|result| result.map(<$vax_type>::to_bits))
+        };
+    }
+    use ConversionResult::{Imprecise, Precise};
+    let pi = "0x3.243f6a8885a308d313198a2e037";
+
+    assert_eq!(vax!(VaxF, "0x1"), Ok(Precise(0x0000_4080)));
+    assert_eq!(vax!(VaxF, "-0x1"), Ok(Precise(0x0000_c080)));
+    assert_eq!(vax!(VaxF, "0x.8"), Ok(Precise(0x0000_4000)));
+    assert_eq!(vax!(VaxF, pi), Ok(Imprecise(0x0fdb_4149)));
+    assert_eq!(vax!(VaxD, pi), Ok(Imprecise(0x68c2_a221_0fda_4149)));
+    assert_eq!(vax!(VaxG, pi), Ok(Imprecise(0x2d18_5444_21fb_4029)));
+    assert_eq!(vax!(VaxG, "0x1"), Ok(Precise(0x0000_0000_0000_4010)));
+
+    // Zero is always positive, because negative zero is the reserved operand.
+    assert_eq!(vax!(VaxF, "-0x0"), Ok(Precise(0x0000_0000)));
+    assert_eq!(VaxF::RESERVED_OPERAND.to_bits(), 0x8000);
+
+    // There are no subnormals, and no infinities.
+    assert_eq!(vax!(VaxF, "0x1p-128"), Ok(Precise(0x0000_0080)));
+    assert_eq!(vax!(VaxF, "0x1p-129"), Ok(Imprecise(0x0000_0000)));
+    assert_eq!(vax!(VaxF, "-0x1.8p-129"), Ok(Imprecise(0x0000_8080)));
+    assert_eq!(vax!(VaxD, "0x1p-200"), Ok(Imprecise(0)));
+    assert_eq!(vax!(VaxF, "0x1.fffffep126"), Ok(Precise(0xffff_7fff)));
+    assert_eq!(vax!(VaxF, "0x1.ffffffp126"), Err(OverflowError));
+    assert_eq!(vax!(VaxG, "-0x1p1023"), Err(OverflowError));
+    assert_eq!(vax!(VaxG, "0x1p1022"), Ok(Precise(0x0000_0000_0000_7ff0)));
+
+    // Overflow is an error even when rounding toward zero.
+    let literal: FloatLiteral = "0x1p127".parse().unwrap();
+    assert_eq!(
+        VaxF::from_literal_with_flags(literal, RoundingMode::TowardZero),
+        Err(OverflowError)
+    );
+    let literal: FloatLiteral = "0x1.000001p0".parse().unwrap();
+    let away = VaxF::from_literal_rounded(literal, RoundingMode::NearestAway);
+    assert_eq!(away, Ok(Imprecise(VaxF::from_bits(0x0001_4080))));
+}
+
+#[test]
+fn test_posit() {
+    macro_rules! posit {
+        ($posit_type:ty, $s:expr, $es:expr) => {
+            <$posit_type>::from_literal($s.parse().unwrap(), $es).map(<$posit_type>::to_bits)
+        };
+    }
+    use ConversionResult::{Imprecise, Precise};
+    use PositEs::*;
+    let pi = "0x3.243f6a8885a308d313198a2e037";
+
+    assert_eq!(posit!(Posit8, "0x0", Es0), Precise(0x00));
+    assert_eq!(posit!(Posit8, "-0x0", Es0), Precise(0x00));
+    assert_eq!(posit!(Posit8, "0x1", Es0), Precise(0x40));
+    assert_eq!(posit!(Posit8, "-0x1", Es0), Precise(0xc0));
+    assert_eq!(posit!(Posit8, pi, Es0), Imprecise(0x69));
+    assert_eq!(posit!(Posit16, "0x1", Es1), Precise(0x4000));
+    assert_eq!(posit!(Posit16, "0x.8", Es1), Precise(0x3000));
+    assert_eq!(posit!(Posit16, pi, Es1), Imprecise(0x5922));
+    assert_eq!(posit!(Posit16, "-0x.8", Es2), Precise(0xc800));
+    assert_eq!(posit!(Posit32, "0x1", Es2), Precise(0x4000_0000));
+    assert_eq!(posit!(Posit32, pi, Es2), Imprecise(0x4c90_fdaa));
+
+    // Ties go to the even encoding.
+    assert_eq!(posit!(Posit8, "0x1.04", Es0), Imprecise(0x40));
+    assert_eq!(posit!(Posit8, "0x1.0c", Es0), Imprecise(0x42));
+    assert_eq!(posit!(Posit8, "0x1.0400001", Es0), Imprecise(0x41));
+
+    // Posits don't round to zero or overflow, and saturate instead.
+    assert_eq!(posit!(Posit8, "0x1p6", Es0), Precise(0x7f));
+    assert_eq!(posit!(Posit8, "0x1.8p6", Es0), Imprecise(0x7f));
+    assert_eq!(posit!(Posit8, "-0x1p100", Es0), Imprecise(0x81));
+    assert_eq!(posit!(Posit8, "0x1p-6", Es0), Precise(0x01));
+    assert_eq!(posit!(Posit8, "0x1p-100", Es0), Imprecise(0x01));
+    assert_eq!(posit!(Posit8, "-0x1p-7", Es0), Imprecise(0xff));
+    assert_eq!(posit!(Posit32, "0x1p120", Es2), Precise(0x7fff_ffff));
+    assert_eq!(posit!(Posit32, "0x1p-121", Es2), Imprecise(0x0000_0001));
+    assert_eq!(posit!(Posit16, "0x1p-29", Es1), Imprecise(0x0001));
+    assert_eq!(Posit16::NAR.to_bits(), 0x8000);
+}
+
+#[test]
+fn test_fixed_point() {
+    fn fixed<I: crate::Integer>(
+        s: &str,
+        fraction_bits: u32,
+        mode: RoundingMode,
+    ) -> Result<ConversionResult<I>, OverflowError> {
+        s.parse::<FloatLiteral>()
+            .unwrap()
+            .to_fixed(fraction_bits, mode)
+    }
+    use ConversionResult::{Imprecise, Precise};
+    use RoundingMode::*;
+
+    // Q15 and Q31 cover [-1, 1).
+    assert_eq!(fixed::<i16>("0x.8", 15, NearestEven), Ok(Precise(0x4000)));
+    assert_eq!(fixed::<i16>("-0x1", 15, NearestEven), Ok(Precise(-0x8000)));
+    assert_eq!(
+        fixed::<i16>("0x.fffe", 15, NearestEven),
+        Ok(Precise(0x7fff))
+    );
+    assert_eq!(fixed::<i16>("0x.ffff", 15, NearestEven), Err(OverflowError));
+    assert_eq!(
+        fixed::<i16>("0x.ffff", 15, TowardZero),
+        Ok(Imprecise(0x7fff))
+    );
+    assert_eq!(fixed::<i16>("0x1", 15, TowardZero), Err(OverflowError));
+    assert_eq!(
+        fixed::<i16>("-0x1.00001", 15, TowardZero),
+        Ok(Imprecise(-0x8000))
+    );
+    assert_eq!(
+        fixed::<i16>("-0x1.00001", 15, TowardNegative),
+        Err(OverflowError)
+    );
+    assert_eq!(
+        fixed::<i32>("0x.c90fdaa22168c", 31, NearestEven),
+        Ok(Imprecise(0x6487_ed51))
+    );
+    assert_eq!(fixed::<i32>("-0x0", 31, NearestEven), Ok(Precise(0)));
+
+    // Ties and the other rounding modes.
+    assert_eq!(fixed::<i8>("0x.18", 4, NearestEven), Ok(Imprecise(2)));
+    assert_eq!(fixed::<i8>("0x.28", 4, NearestEven), Ok(Imprecise(2)));
+    assert_eq!(fixed::<i8>("0x.28", 4, NearestAway), Ok(Imprecise(3)));
+    assert_eq!(fixed::<i8>("-0x.28", 4, TowardPositive), Ok(Imprecise(-2)));
+    assert_eq!(fixed::<i8>("-0x.28", 4, TowardNegative), Ok(Imprecise(-3)));
+
+    // Unsigned types can't hold negative numbers, unless they round to zero.
+    assert_eq!(fixed::<u16>("0x1.8", 8, NearestEven), Ok(Precise(0x180)));
+    assert_eq!(fixed::<u16>("0xff.ff", 8, NearestEven), Ok(Precise(0xffff)));
+    assert_eq!(fixed::<u16>("0xff.ff8", 8, NearestEven), Err(OverflowError));
+    assert_eq!(fixed::<u16>("-0x1p-8", 8, NearestEven), Err(OverflowError));
+    assert_eq!(fixed::<u16>("-0x1p-10", 8, NearestEven), Ok(Imprecise(0)));
+    assert_eq!(
+        fixed::<u128>("0x1p127", 0, NearestEven),
+        Ok(Precise(1 << 127))
+    );
+    assert_eq!(fixed::<u128>("0x1p128", 0, NearestEven), Err(OverflowError));
+    assert_eq!(
+        fixed::<i128>("-0x1p127", 0, NearestEven),
+        Ok(Precise(i128::MIN))
+    );
+    assert_eq!(fixed::<i128>("0x1p127", 0, NearestEven), Err(OverflowError));
+}
+
+#[test]
+fn test_integers_exact() {
+    use ConversionResult::{Imprecise, Precise};
+
+    assert_eq!(literal("0xDEADBEEF").to_integer::<u32>(), Ok(0xdead_beef));
+    assert_eq!(literal("0xDEADBEEF").to_integer::<i64>(), Ok(0xdead_beef));
+    assert_eq!(
+        literal("0xDEADBEEF").to_integer::<i32>(),
+        Err(IntegerError::Overflow)
+    );
+    assert_eq!(literal("0x1p100").to_integer::<u128>(), Ok(1 << 100));
+    assert_eq!(
+        literal("0x1p100").to_integer::<u64>(),
+        Err(IntegerError::Overflow)
+    );
+    assert_eq!(literal("0x.4p10").to_integer::<u16>(), Ok(0x100));
+    assert_eq!(literal("-0x80").to_integer::<i8>(), Ok(-0x80));
+    assert_eq!(
+        literal("-0x81").to_integer::<i8>(),
+        Err(IntegerError::Overflow)
+    );
+    assert_eq!(
+        literal("-0x1").to_integer::<usize>(),
+        Err(IntegerError::Overflow)
+    );
+    assert_eq!(literal("-0x0").to_integer::<u8>(), Ok(0));
+    assert_eq!(
+        literal("0x1.8").to_integer::<u8>(),
+        Err(IntegerError::NotAnInteger)
+    );
+    assert_eq!(
+        literal("0x1p-1000").to_integer::<u8>(),
+        Err(IntegerError::NotAnInteger)
+    );
+    assert_eq!(
+        literal("0xffffffffffffffffffffffffffffffff").to_integer::<u128>(),
+        Ok(u128::MAX)
+    );
+    assert_eq!(
+        literal("0x1p128").to_integer::<u128>(),
+        Err(IntegerError::Overflow)
+    );
+
+    let x = literal("-0x2.8");
+    assert_eq!(x.to_integer_floor::<i32>(), Ok(Imprecise(-3)));
+    assert_eq!(x.to_integer_ceil::<i32>(), Ok(Imprecise(-2)));
+    assert_eq!(x.to_integer_trunc::<i32>(), Ok(Imprecise(-2)));
+    assert_eq!(x.to_integer_nearest::<i32>(), Ok(Imprecise(-2)));
+    assert_eq!(
+        literal("0x3.8").to_integer_nearest::<i32>(),
+        Ok(Imprecise(4))
+    );
+    assert_eq!(literal("0x3").to_integer_floor::<u8>(), Ok(Precise(3)));
+    assert_eq!(literal("-0x.8").to_integer_ceil::<u8>(), Ok(Imprecise(0)));
+    assert_eq!(
+        literal("-0x.8").to_integer_floor::<u8>(),
+        Err(OverflowError)
+    );
+    assert_eq!(
+        literal("0xff.8").to_integer_trunc::<u8>(),
+        Ok(Imprecise(0xff))
+    );
+    assert_eq!(
+        literal("0xff.8").to_integer_ceil::<u8>(),
+        Err(OverflowError)
+    );
+}
+
+#[test]
+fn test_exact_value() {
+    let exact = literal("0x1.8p1").to_exact::<u32>();
+    assert_eq!(
+        (exact.is_positive, exact.mantissa, exact.exponent),
+        (true, vec![3], 0)
+    );
+    let exact = literal("0x12345678.9abcdef01").to_exact::<u32>();
+    assert_eq!(exact.mantissa, [0xabcd_ef01, 0x2345_6789, 0x1]);
+    assert_eq!(exact.exponent, -36);
+    let exact = literal("0x12345678.9abcdef01").to_exact::<u64>();
+    assert_eq!(exact.mantissa, [0x2345_6789_abcd_ef01, 0x1]);
+    let exact = literal("-0x1p-2147483648").to_exact::<u64>();
+    assert_eq!(
+        (exact.is_positive, exact.mantissa, exact.exponent),
+        (false, vec![1], -2147483648)
+    );
+    let exact = literal("0x8p2147483647").to_exact::<u64>();
+    assert_eq!(exact.exponent, 2147483650);
+    assert_eq!(
+        literal("-0x0.000").to_exact::<u32>(),
+        ExactValue {
+            is_positive: false,
+            mantissa: vec![],
+            exponent: 0,
+        }
+    );
+
+    assert_eq!(
+        literal("-0x.c").to_fraction::<u32>(),
+        ExactFraction {
+            is_positive: false,
+            numerator: vec![3],
+            denominator: vec![4],
+        }
+    );
+    let fraction = literal("0x1p100").to_fraction::<u64>();
+    assert_eq!(fraction.numerator, [0, 1 << 36]);
+    assert_eq!(fraction.denominator, [1]);
+    let fraction = literal("0x3p-70").to_fraction::<u32>();
+    assert_eq!(fraction.numerator, [3]);
+    assert_eq!(fraction.denominator, [0, 0, 0x40]);
+    let fraction = literal("0x0").to_fraction::<u64>();
+    assert!(fraction.numerator.is_empty());
+    assert_eq!(fraction.denominator, [1]);
+}
+
+#[test]
+fn test_decimal() {
+    use RoundingMode::*;
+
+    assert_eq!(literal("0x1p-1").to_decimal_string(), "0.5");
+    assert_eq!(
+        literal("0x1p64").to_decimal_string(),
+        "18446744073709551616"
+    );
+    assert_eq!(literal("0x1.8p1").to_decimal_string(), "3");
+    assert_eq!(literal("-0x10").to_decimal_string(), "-16");
+    assert_eq!(literal("-0x0").to_decimal_string(), "-0");
+    assert_eq!(literal("0x0").to_decimal_string(), "0");
+    assert_eq!(literal("0x1.4p-3").to_decimal_string(), "0.15625");
+    assert_eq!(
+        literal("0xa.00000000001p20").to_decimal_string(),
+        "10485760.000000059604644775390625"
+    );
+
+    // Rounding in the last digit, and carries out of it.
+    let x = literal("0x1.99999ap-4");
+    assert_eq!(x.to_decimal_exponential(3, TowardZero), "1.000e-01");
+    assert_eq!(x.to_decimal_exponential(3, TowardPositive), "1.001e-01");
+    assert_eq!(
+        literal("-0x1.99999ap-4").to_decimal_exponential(3, TowardNegative),
+        "-1.001e-01"
+    );
+    assert_eq!(literal("0x9.fc").to_decimal_fixed(1, NearestEven), "10.0");
+    assert_eq!(
+        literal("0x9.fc").to_decimal_exponential(0, NearestEven),
+        "1e+01"
+    );
+    assert_eq!(
+        literal("0x9.fc").to_decimal_exponential(1, TowardZero),
+        "9.9e+00"
+    );
+    assert_eq!(literal("0x1p-10").to_decimal_fixed(2, NearestEven), "0.00");
+    assert_eq!(
+        literal("0x1p-10").to_decimal_fixed(2, TowardPositive),
+        "0.01"
+    );
+    assert_eq!(
+        literal("-0x1p-10").to_decimal_fixed(0, TowardNegative),
+        "-1"
+    );
+    assert_eq!(literal("0x2.8").to_decimal_fixed(0, NearestEven), "2");
+    assert_eq!(literal("0x2.8").to_decimal_fixed(0, NearestAway), "3");
+    assert_eq!(literal("0x3.8").to_decimal_fixed(0, NearestEven), "4");
+    assert_eq!(
+        literal("0x1p-1000").to_decimal_exponential(2, NearestEven),
+        "9.33e-302"
+    );
+    assert_eq!(
+        literal("0x1p1000").to_decimal_exponential(2, NearestEven),
+        "1.07e+301"
+    );
+    assert_eq!(
+        literal("-0x0").to_decimal_exponential(2, NearestEven),
+        "-0.00e+00"
+    );
+
+    #[cfg(feature = "std")]
+    {
+        // Round to nearest matches printf.
+        let values = [
+            0.1,
+            1.0 / 3.0,
+            2.5,
+            0.5,
+            1e300,
+            5e-324,
+            2.2250738585072014e-308,
+            123456.789,
+            9.9999,
+            0.0,
+            -0.0,
+            -1.5,
+            f64::MAX,
+            1e22,
+            0.125,
+            9.5,
+            10.5,
+            0.000123,
+            999.9996,
+        ];
+        for &f in values.iter() {
+            let x = f64_literal(f);
+            for &precision in [0, 1, 2, 3, 5, 8, 15, 16, 17, 20, 40].iter() {
+                assert_eq!(
+                    x.to_decimal_exponential(precision, NearestEven),
+                    libc_funcs::format_f64("%.*e", precision, f)
+                );
+                assert_eq!(
+                    x.to_decimal_fixed(precision, NearestEven),
+                    libc_funcs::format_f64("%.*f", precision, f)
+                );
+            }
+        }
+        let full = libc_funcs::format_f64("%.*f", 1074, 5e-324);
+        assert_eq!(
+            f64_literal(5e-324).to_decimal_string(),
+            full.trim_end_matches('0')
+        );
+    }
+}
+
+#[test]
+fn test_decimal_large_exponents() {
+    use crate::decimal::Decimal;
+    use crate::exact::BigUint;
+    use RoundingMode::*;
+
+    // Only the digits that are printed are computed, so huge exponents are
+    // fast.
+    let tiny = literal("0x1p-2147483648");
+    assert_eq!(
+        tiny.to_decimal_exponential(20, NearestEven),
+        "5.67661552600373134382e-646456994"
+    );
+    assert_eq!(tiny.to_decimal_fixed(3, NearestEven), "0.000");
+    assert_eq!(tiny.to_decimal_fixed(3, TowardPositive), "0.001");
+    assert_eq!(
+        literal("-0x1.8p2147483647").to_decimal_exponential(20, NearestEven),
+        "-1.32120978876297251491e+646456993"
+    );
+
+    // Check against the exact expansion, including values that are a multiple
+    // of a large power of ten.
+    let mut five_powers = BigUint::from_u128(1);
+    for _ in 0..1100 {
+        five_powers = five_powers.mul_small(5);
+    }
+    let multiple = Dyadic {
+        is_positive: true,
+        mantissa: five_powers,
+        exponent: 1200,
+    }
+    .to_literal();
+    let modes = [NearestEven, NearestAway, TowardZero, TowardPositive];
+    let mut random = XorShift::new();
+    for index in 0..200 {
+        let literal = if index == 0 {
+            multiple.clone()
+        } else {
+            let mantissa = random.next() >> (random.next() % 64);
+            let exponent = (random.next() % 12000) as i64 - 6000;
+            Dyadic {
+                is_positive: random.next() & 1 == 0,
+                mantissa: BigUint::from_u128(mantissa as u128),
+                exponent,
+            }
+            .to_literal()
+        };
+        let exact = Decimal::from_literal(&literal);
+        let precision = (random.next() % 40) as usize;
+        let mode = modes[(random.next() % 4) as usize];
+        assert_eq!(
+            literal.to_decimal_exponential(precision, mode),
+            exact.to_exponential(precision, mode),
+            "{}",
+            literal
+        );
+        assert_eq!(
+            literal.to_decimal_fixed(precision, mode),
+            exact.to_fixed(precision, mode),
+            "{}",
+            literal
+        );
+    }
+}
+
+#[test]
+fn test_shortest_decimal() {
+    let x = literal("0x1.99999ap-4");
+    assert_eq!(x.to_shortest_decimal::<f32>().unwrap(), "1e-1");
+    assert_eq!(x.to_shortest_decimal::<F16>().unwrap(), "1e-1");
+    assert_eq!(
+        x.to_shortest_decimal::<f64>().unwrap(),
+        "1.0000000149011612e-1"
+    );
+    assert_eq!(
+        literal("-0x0").to_shortest_decimal::<f64>().unwrap(),
+        "-0e0"
+    );
+    assert_eq!(literal("0x1p1024").to_shortest_decimal::<f64>(), None);
+    // Without infinities, everything above the largest number saturates to
+    // it, so there is no upper limit.
+    assert_eq!(
+        literal("0x1.cp8").to_shortest_decimal::<F8E4M3>().unwrap(),
+        "5e2"
+    );
+    assert_eq!(
+        literal("0x1.cp8").to_shortest_decimal::<F8E5M2>().unwrap(),
+        "4.5e2"
+    );
+    assert_eq!(
+        literal("-0x1p-30")
+            .to_shortest_decimal::<F8E4M3Fnuz>()
+            .unwrap(),
+        "0e0"
+    );
+
+    assert!(x.is_shortest_decimal::<f32>("1e-1"));
+    assert!(x.is_shortest_decimal::<f32>(".1"));
+    assert!(x.is_shortest_decimal::<f32>("0.10E0"));
+    assert!(!x.is_shortest_decimal::<f32>("-0.1"));
+    assert!(!x.is_shortest_decimal::<f32>("0.10000001"));
+    assert!(!x.is_shortest_decimal::<f32>("0.2"));
+    assert!(!x.is_shortest_decimal::<f32>("0.1x"));
+    assert!(!x.is_shortest_decimal::<f32>("e1"));
+    // Without subnormals, the smallest normal number gets everything from
+    // half of it, where flushing to zero starts.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Flush(u32);
+    impl core::ops::Neg for Flush {
+        type Output = Flush;
+        fn neg(self) -> Flush {
+            Flush(self.0 ^ 0x8000_0000)
+        }
+    }
+    impl FPFormat for Flush {
+        const FORMAT: FormatDescriptor = FormatDescriptor {
+            has_subnormals: false,
+            ..FormatDescriptor::ieee(8, 23)
+        };
+        fn from_bits(bits: u128) -> Flush {
+            Flush(bits as u32)
+        }
+    }
+    let x = literal("0x1p-126");
+    assert_eq!(x.to_shortest_decimal::<Flush>().unwrap(), "1e-38");
+    assert!(x.is_shortest_decimal::<Flush>("1e-38"));
+    assert_eq!(
+        literal("0x1.b38fb9daa78e4p-127").convert::<Flush>().inner(),
+        Flush(0x0080_0000)
+    );
+    // The tie at half of the smallest normal number rounds to zero, so it
+    // isn't in the interval.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Small(u8);
+    impl core::ops::Neg for Small {
+        type Output = Small;
+        fn neg(self) -> Small {
+            Small(self.0 ^ 0x80)
+        }
+    }
+    impl FPFormat for Small {
+        const FORMAT: FormatDescriptor = FormatDescriptor {
+            exponent_bias: -3,
+            has_subnormals: false,
+            ..FormatDescriptor::ieee(4, 3)
+        };
+        fn from_bits(bits: u128) -> Small {
+            Small(bits as u8)
+        }
+    }
+    let x = literal("0x10");
+    assert_eq!(literal("0x8").convert::<Small>().inner(), Small(0));
+    assert_eq!(x.to_shortest_decimal::<Small>().unwrap(), "1e1");
+    assert!(x.is_shortest_decimal::<Small>("9"));
+    assert!(!x.is_shortest_decimal::<Small>("8"));
+    assert!(!literal("0x0").is_shortest_decimal::<Small>("9"));
+    assert!(literal("0x0").is_shortest_decimal::<Small>("0"));
+
+    // Both of these are shortest, although only one is closest.
+    let x = literal("0x1p-1074");
+    assert!(x.is_shortest_decimal::<f64>("5e-324"));
+    assert!(x.is_shortest_decimal::<f64>("4e-324"));
+    assert!(!x.is_shortest_decimal::<f64>("2e-324"));
+
+    // Rust formats floats with the shortest decimal too.
+    let mut random = XorShift::new();
+    let mut values = vec![
+        1.0,
+        0.3,
+        1e23,
+        5e-324,
+        f64::MAX,
+        f64::MIN_POSITIVE,
+        2f64.powi(-1022) * 2.0,
+    ];
+    values.extend(
+        (0..2000)
+            .map(|_| f64::from_bits(random.next()))
+            .filter(|f| f.is_finite()),
+    );
+    for &f in values.iter() {
+        let x = f64_literal(f);
+        let shortest = format!("{:e}", f);
+        assert_eq!(x.to_shortest_decimal::<f64>().unwrap(), shortest);
+        assert!(x.is_shortest_decimal::<f64>(&shortest));
+        assert!(x.is_shortest_decimal::<f64>(&format!("{}", f)));
+
+        let f = f32::from_bits(random.next() as u32);
+        if f.is_finite() {
+            let x = f64_literal(f as f64);
+            assert_eq!(x.to_shortest_decimal::<f32>().unwrap(), format!("{:e}", f));
+        }
+    }
+}
+
+#[test]
+fn test_hex_format() {
+    let plain = HexFormatOptions::default();
+    assert_eq!(plain.format_f64(1.0), "0x1p+0");
+    assert_eq!(plain.format_f64(-0.0), "-0x0p+0");
+    assert_eq!(plain.format_f64(5e-324), "0x0.0000000000001p-1022");
+    assert_eq!(plain.format_f32(1e-45), "0x1p-149");
+    assert_eq!(plain.format_f64(f64::NEG_INFINITY), "-inf");
+    let rounded = HexFormatOptions {
+        precision: Some(1),
+        ..plain
+    };
+    assert_eq!(rounded.format_f64(1.96875), "0x2.0p+0");
+    assert_eq!(rounded.format_f64(1.03125), "0x1.0p+0");
+    assert_eq!(rounded.format_f64(1.09375), "0x1.2p+0");
+
+    #[cfg(feature = "std")]
+    {
+        // Every combination of options matches printf.
+        let values = [
+            0.0,
+            -0.0,
+            1.0,
+            -1.5,
+            0.1,
+            1.0 / 3.0,
+            1e300,
+            -2.5e-310,
+            5e-324,
+            f64::MIN_POSITIVE,
+            f64::MAX,
+            1.96875,
+            1.03125,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::NAN,
+            -f64::NAN,
+            f64::from(0.1f32),
+            f64::from(f32::MIN_POSITIVE / 3.0),
+        ];
+        let precisions = [
+            None,
+            Some(0),
+            Some(1),
+            Some(2),
+            Some(6),
+            Some(12),
+            Some(13),
+            Some(20),
+        ];
+        for flags in 0..64 {
+            for &width in [0, 8, 30].iter() {
+                for &precision in precisions.iter() {
+                    let options = HexFormatOptions {
+                        precision,
+                        uppercase: flags & 1 != 0,
+                        plus_sign: flags & 2 != 0,
+                        space_sign: flags & 4 != 0,
+                        alternate: flags & 8 != 0,
+                        width,
+                        zero_pad: flags & 16 != 0,
+                        left_align: flags & 32 != 0,
+                    };
+                    let mut format = String::from("%");
+                    for (flag, c) in [(2, '+'), (4, ' '), (8, '#'), (16, '0'), (32, '-')].iter() {
+                        if flags & flag != 0 {
+                            format.push(*c);
+                        }
+                    }
+                    if width > 0 {
+                        format.push_str(&width.to_string());
+                    }
+                    if let Some(precision) = precision {
+                        format.push_str(&format!(".{}", precision));
+                    }
+                    format.push(if options.uppercase { 'A' } else { 'a' });
+
+                    for &f in values.iter() {
+                        assert_eq!(
+                            options.format_f64(f),
+                            libc_funcs::format_f64_without_precision(&format, f),
+                            "{} {:e}",
+                            format,
+                            f
+                        );
+                        assert_eq!(
+                            options.format_f32(f as f32),
+                            libc_funcs::format_f64_without_precision(&format, f as f32 as f64)
+                        );
+                    }
+                }
+            }
+        }
+
+        // Rounding at every precision.
+        let mut random = XorShift::new();
+        for _ in 0..500 {
+            let state = random.next();
+            // Shifting some of them gives small and subnormal numbers.
+            let f = f64::from_bits(state >> ((state % 3) * 8));
+            for precision in 0..15 {
+                let options = HexFormatOptions {
+                    precision: Some(precision),
+                    ..plain
+                };
+                assert_eq!(
+                    options.format_f64(f),
+                    libc_funcs::format_f64("%.*a", precision, f)
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_display() {
+    let cases = [
+        ("0x1", "0x1p+0", "0x1p+0"),
+        ("-0x18p-1", "-0x1.8p+3", "-0x18p-1"),
+        ("0x0.0018p3", "0x1.8p-9", "0x0.0018p+3"),
+        ("0x1800", "0x1.8p+12", "0x1800p+0"),
+        ("0x00a.b0p+0", "0x1.56p+3", "0xa.bp+0"),
+        ("0x.8", "0x1p-1", "0x0.8p+0"),
+        ("0xF.FFFFFp-1", "0x1.fffffep+2", "0xf.fffffp-1"),
+        ("0x0", "0x0p+0", "0x0p+0"),
+        ("-0x0.000p99", "-0x0p+0", "-0x0p+99"),
+        // Normalizing would overflow the exponent, so the digits are kept.
+        ("0x10p2147483647", "0x10p+2147483647", "0x10p+2147483647"),
+        ("0x.1p-2147483648", "0x0.1p-2147483648", "0x0.1p-2147483648"),
+    ];
+    for &(s, normalized, grouped) in cases.iter() {
+        let x = literal(s);
+        assert_eq!(x.to_string(), normalized);
+        assert_eq!(format!("{:#}", x), grouped);
+        assert_eq!(literal(normalized), x);
+        assert_eq!(literal(grouped), x);
+    }
+
+    assert_eq!(literal("0x1p4"), literal("0x10"));
+    assert_ne!(literal("0x1p4"), literal("0x10.1"));
+    assert_ne!(literal("0x0"), literal("-0x0"));
+
+    // Every f64 round trips through both forms.
+    let mut random = XorShift::new();
+    for _ in 0..1000 {
+        let f = f64::from_bits(random.next());
+        if !f.is_finite() {
+            continue;
+        }
+        let x = f64_literal(f);
+        assert_eq!(literal(&x.to_string()), x);
+        assert_eq!(literal(&format!("{:#}", x)), x);
+        assert_eq!(
+            literal(&x.to_string()).convert::<f64>().inner().to_bits(),
+            f.to_bits()
+        );
+    }
+}
+
+#[test]
+fn test_exact_hex_format() {
+    use SubnormalStyle::*;
+
+    assert_eq!(format_f64_exact(0.0, Normalized), "0x0p+0");
+    assert_eq!(format_f64_exact(-0.0, Denormalized), "-0x0p+0");
+    assert_eq!(format_f64_exact(-1.5, Normalized), "-0x1.8p+0");
+    assert_eq!(
+        format_f64_exact(f64::MAX, Normalized),
+        "0x1.fffffffffffffp+1023"
+    );
+    assert_eq!(format_f64_exact(f64::NEG_INFINITY, Normalized), "-inf");
+    assert_eq!(format_f64_exact(f64::NAN, Normalized), "nan");
+    assert_eq!(format_f32_exact(0.1, Normalized), "0x1.99999ap-4");
+    assert_eq!(format_f32_exact(f32::MAX, Normalized), "0x1.fffffep+127");
+    assert_eq!(
+        format_f32_exact(f32::MIN_POSITIVE, Denormalized),
+        "0x1p-126"
+    );
+    let subnormal = f32::from_bits(0x0040_0001);
+    assert_eq!(format_f32_exact(subnormal, Denormalized), "0x0.800002p-126");
+    assert_eq!(format_f32_exact(subnormal, Normalized), "0x1.000004p-127");
+    let subnormal = f64::from_bits(0x000c_0000_0000_0000);
+    assert_eq!(format_f64_exact(subnormal, Denormalized), "0x0.cp-1022");
+    assert_eq!(format_f64_exact(subnormal, Normalized), "0x1.8p-1023");
+
+    let mut random = XorShift::new();
+    for _ in 0..2000 {
+        let state = random.next();
+        // Shifting some of them gives small and subnormal numbers.
+        let f = f64::from_bits(state >> ((state % 4) * 4));
+        let g = f32::from_bits((state >> ((state % 4) * 3)) as u32);
+
+        // The denormalized style matches printf for `f64`.
+        #[cfg(feature = "std")]
+        assert_eq!(
+            format_f64_exact(f, Denormalized),
+            libc_funcs::format_f64_without_precision("%a", f)
+        );
+        for &style in [Normalized, Denormalized].iter() {
+            if f.is_finite() {
+                let literal: FloatLiteral = format_f64_exact(f, style).parse().unwrap();
+                assert_eq!(literal, f64_literal(f));
+            }
+            if g.is_finite() {
+                let literal: FloatLiteral = format_f32_exact(g, style).parse().unwrap();
+                let converted = literal.convert::<f32>();
+                assert_eq!(converted.inner().to_bits(), g.to_bits());
+                assert!(converted.is_precise());
+            }
+        }
+    }
+}
+
+fn mx_block(literals: &[&str], element: MxElement) -> MxBlock {
+    let literals: Vec<FloatLiteral> = literals.iter().map(|s| s.parse().unwrap()).collect();
+    MxBlock::from_literals(&literals, element).unwrap()
+}
+
+#[test]
+fn test_mx_block() {
+    let block = mx_block(
+        &["0x6", "0x1", "0x.8", "0x.4", "0x5", "-0x3"],
+        MxElement::Fp4E2M1,
+    );
+    assert_eq!(block.scale, 127);
+    assert_eq!(&block.elements[..6], &[0x7, 0x2, 0x1, 0x0, 0x6, 0xd]);
+    assert_eq!(
+        &block.inexact[..6],
+        &[false, false, false, true, true, false]
+    );
+    // The rest of the block is padded with zeros.
+    assert!(block.elements[6..].iter().all(|&e| e == 0));
+    assert!(block.inexact[6..].iter().all(|&i| !i));
+
+    // The scale is a power of two, so elements keep their precision.
+    let block = mx_block(&["0x1.8p-40", "0x1p-41"], MxElement::Fp4E2M1);
+    assert_eq!(block.scale, 127 - 42);
+    assert_eq!(&block.elements[..2], &[0x7, 0x4]);
+
+    // Rounding up past the largest value saturates.
+    let block = mx_block(&["0x1.fp2"], MxElement::Fp4E2M1);
+    assert_eq!((block.elements[0], block.inexact[0]), (0x7, true));
+    let block = mx_block(&["-0x1.fp15"], MxElement::Fp8E5M2);
+    assert_eq!(block.scale, 127);
+    assert_eq!((block.elements[0], block.inexact[0]), (0xfb, true));
+    let block = mx_block(&["0x1.ep2"], MxElement::Fp6E2M3);
+    assert_eq!((block.elements[0], block.inexact[0]), (0x1f, false));
+    let block = mx_block(&["0x1.cp4", "0x1.ep4"], MxElement::Fp6E3M2);
+    assert_eq!(&block.elements[..2], &[0x1f, 0x1f]);
+    assert_eq!(&block.inexact[..2], &[false, true]);
+
+    // The scale is limited to the range of E8M0.
+    let block = mx_block(&["0x1p200", "0x1p100"], MxElement::Fp8E4M3);
+    assert_eq!(block.scale, 254);
+    assert_eq!(&block.elements[..2], &[0x7e, 0x00]);
+    let block = mx_block(&["0x1p-200"], MxElement::Fp8E4M3);
+    assert_eq!(block.scale, 0);
+    assert_eq!((block.elements[0], block.inexact[0]), (0x00, true));
+
+    let block = mx_block(&["0x0", "-0x0"], MxElement::Fp8E4M3);
+    assert_eq!(block.scale, 0);
+    assert_eq!(&block.elements[..2], &[0x00, 0x80]);
+    assert_eq!(mx_block(&[], MxElement::Fp8E5M2).elements, [0; 32]);
+}
+
+#[test]
+fn test_mx_block_too_large() {
+    let literals = vec![literal("0x1"); 33];
+    assert_eq!(
+        MxBlock::from_literals(&literals, MxElement::Fp8E4M3),
+        Err(BlockSizeError { len: 33 })
+    );
+    assert!(MxBlock::from_literals(&literals[..32], MxElement::Fp8E4M3).is_ok());
+}
+
 #[test]
 fn test_imprecise_conversions() {
     test_imprecise("0x1p-10000"); // Underflow
+    test_imprecise("0x1p-150"); // Subnormal rounding
     test_imprecise("0x123456789abcdef"); // Truncation
     test_imprecise("0x1p10000"); // Overflow
 }
@@ -341,37 +2126,35 @@ fn test_convert_hexf64() {
         convert_hexf64(false, 0x001f_ffff_ffff_ffff, 0),
         Precise(9007199254740991.0)
     );
-    // This mantissas is "too big" but we report it as Precise.
     assert_eq!(
         convert_hexf64(false, 0x003f_ffff_ffff_ffff, 0),
-        Precise(1.8014398509481982e16)
+        Imprecise(1.8014398509481984e16)
     );
     assert_eq!(
         convert_hexf64(false, 0xffff_ffff_ffff_f800, -11),
-        Imprecise(9007199254740991.0)
+        Precise(9007199254740991.0)
     );
     assert_eq!(
         convert_hexf64(false, 0xffff_ffff_ffff_fc00, -11),
-        Imprecise(9007199254740991.0)
+        Imprecise(9007199254740992.0)
     );
 
     // denormal truncation
-    // TODO: denormals are not supported yet.
-    //assert!(convert_hexf64(false, 0x000f_ffff_ffff_ffff, -1074).is_precise());
-    //assert!(convert_hexf64(false, 0x001f_ffff_ffff_ffff, -1075).is_imprecise());
-    //assert!(convert_hexf64(false, 0x001f_ffff_ffff_fffe, -1075).is_precise());
-    //assert!(convert_hexf64(false, 0xffff_ffff_ffff_f800, -1086).is_imprecise());
-    //assert!(convert_hexf64(false, 0xffff_ffff_ffff_f000, -1086).is_precise());
+    assert!(convert_hexf64(false, 0x000f_ffff_ffff_ffff, -1074).is_precise());
+    assert!(convert_hexf64(false, 0x001f_ffff_ffff_ffff, -1075).is_imprecise());
+    assert!(convert_hexf64(false, 0x001f_ffff_ffff_fffe, -1075).is_precise());
+    assert!(convert_hexf64(false, 0xffff_ffff_ffff_f800, -1086).is_imprecise());
+    assert!(convert_hexf64(false, 0xffff_ffff_ffff_f000, -1086).is_precise());
 
     // minimum
-    //assert!(convert_hexf64(false, 0x0000_0000_0000_0001, -1074).is_precise());
-    //assert!(convert_hexf64(false, 0x0000_0000_0000_0001, -1075).is_imprecise());
-    //assert!(convert_hexf64(false, 0x0000_0000_0000_0002, -1075).is_precise());
-    //assert!(convert_hexf64(false, 0x0000_0000_0000_0002, -1076).is_imprecise());
-    //assert!(convert_hexf64(false, 0x0000_0000_0000_0003, -1075).is_imprecise());
-    //assert!(convert_hexf64(false, 0x0000_0000_0000_0003, -1076).is_imprecise());
-    //assert!(convert_hexf64(false, 0x8000_0000_0000_0000, -1137).is_precise());
-    //assert!(convert_hexf64(false, 0x8000_0000_0000_0000, -1138).is_imprecise());
+    assert!(convert_hexf64(false, 0x0000_0000_0000_0001, -1074).is_precise());
+    assert!(convert_hexf64(false, 0x0000_0000_0000_0001, -1075).is_imprecise());
+    assert!(convert_hexf64(false, 0x0000_0000_0000_0002, -1075).is_precise());
+    assert!(convert_hexf64(false, 0x0000_0000_0000_0002, -1076).is_imprecise());
+    assert!(convert_hexf64(false, 0x0000_0000_0000_0003, -1075).is_imprecise());
+    assert!(convert_hexf64(false, 0x0000_0000_0000_0003, -1076).is_imprecise());
+    assert!(convert_hexf64(false, 0x8000_0000_0000_0000, -1137).is_precise());
+    assert!(convert_hexf64(false, 0x8000_0000_0000_0000, -1138).is_imprecise());
 
     // maximum
     assert_eq!(
@@ -388,12 +2171,11 @@ fn test_convert_hexf64() {
     );
     assert_eq!(
         convert_hexf64(false, 0xffff_ffff_ffff_f800, 960),
-        // TODO: this should be precise.
-        Imprecise(f64::MAX)
+        Precise(f64::MAX)
     );
     assert_eq!(
         convert_hexf64(false, 0xffff_ffff_ffff_fc00, 960),
-        Imprecise(f64::MAX)
+        Imprecise(f64::INFINITY)
     );
 }
 
@@ -415,12 +2197,32 @@ fn test_consumed() {
     assert_eq!(consumed("-0x0p3    "), 6);
 }
 
+#[test]
+fn test_from_bytes() {
+    let (literal, consumed) = FloatLiteral::from_bytes(b"-0x3.14p+3\xc3\x28").unwrap();
+    assert_eq!(consumed, 10);
+    assert_eq!(literal.convert::<f64>().inner(), -24.625);
+
+    // Bytes that aren't ASCII end the literal, even if they look like digits
+    // or separators in Latin-1.
+    let (literal, consumed) = FloatLiteral::from_bytes(b"0xA\xb7\xbd").unwrap();
+    assert_eq!(consumed, 3);
+    assert_eq!(literal.convert::<f32>().inner(), 10.0);
+
+    let error = FloatLiteral::from_bytes(b"\xff0x1").unwrap_err();
+    assert_eq!(error, ParseErrorKind::MissingPrefix.at(0));
+    let error = FloatLiteral::from_bytes(b"0x1p\xb9").unwrap_err();
+    assert_eq!(error, ParseErrorKind::MissingExponent.at(4));
+    let error = FloatLiteral::from_bytes(b"").unwrap_err();
+    assert_eq!(error, ParseErrorKind::MissingPrefix.at(0));
+}
+
 #[cfg(feature = "std")]
 mod libc_funcs {
     use std::ffi;
 
     // I had both of these functions checked over by jynelson
-    #[allow(unsafe_code, dead_code)]
+    #[allow(unsafe_code, dead_code, clippy::manual_range_contains)]
     pub fn f64_to_string(f: f64) -> Result<Vec<u8>, ()> {
         let mut dest = [0u8; 32];
         let format = ffi::CString::new("%a").unwrap();
@@ -434,6 +2236,39 @@ mod libc_funcs {
         }
     }
 
+    #[allow(unsafe_code)]
+    pub fn format_f64(format: &str, precision: usize, f: f64) -> String {
+        let mut dest = [0u8; 4096];
+        let format = ffi::CString::new(format).unwrap();
+        let check = unsafe {
+            libc::snprintf(
+                dest.as_mut_ptr() as *mut i8,
+                dest.len(),
+                format.as_ptr(),
+                precision as libc::c_int,
+                f as libc::c_double,
+            )
+        };
+        assert!((0..dest.len() as i32).contains(&check));
+        String::from_utf8(dest[..check as usize].to_vec()).unwrap()
+    }
+
+    #[allow(unsafe_code)]
+    pub fn format_f64_without_precision(format: &str, f: f64) -> String {
+        let mut dest = [0u8; 4096];
+        let format = ffi::CString::new(format).unwrap();
+        let check = unsafe {
+            libc::snprintf(
+                dest.as_mut_ptr() as *mut i8,
+                dest.len(),
+                format.as_ptr(),
+                f as libc::c_double,
+            )
+        };
+        assert!((0..dest.len() as i32).contains(&check));
+        String::from_utf8(dest[..check as usize].to_vec()).unwrap()
+    }
+
     #[allow(unsafe_code)]
     pub fn string_to_f32(string: &[u8]) -> Result<f32, ()> {
         let source = ffi::CString::new(string).unwrap();
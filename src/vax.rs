@@ -0,0 +1,99 @@
+//! DEC VAX floating point.
+
+use crate::fpformat::{VAX_D, VAX_F, VAX_G};
+use crate::rounding::RoundingMode;
+use crate::{Conversion, ConversionResult, FloatLiteral, OverflowError};
+
+/// Reverse the order of the 16-bit words in `bits`. VAX numbers are stored
+/// with the most significant word first, and each word is little endian.
+fn swap_words(bits: u128, words: u32) -> u128 {
+    (0..words).fold(0, |swapped, index| {
+        let word = (bits >> (16 * index)) & 0xffff;
+        swapped | word << (16 * (words - 1 - index))
+    })
+}
+
+macro_rules! vax_type {
+    ($name:ident, $bits_type:ty, $format:expr) => {
+        impl $name {
+            /// The reserved operand, which has the bits of negative zero.
+            pub const RESERVED_OPERAND: $name = $name(0x8000);
+
+            /// Create a number from its bits, as read from memory as a little
+            /// endian integer.
+            pub const fn from_bits(bits: $bits_type) -> $name {
+                $name(bits)
+            }
+
+            /// Get the bits of the number, as written to memory as a little
+            /// endian integer.
+            pub const fn to_bits(self) -> $bits_type {
+                self.0
+            }
+
+            /// Convert a literal to this format, rounding to nearest, ties to
+            /// even. Returns an error if the literal is too large.
+            pub fn from_literal(
+                literal: FloatLiteral,
+            ) -> Result<ConversionResult<$name>, OverflowError> {
+                $name::from_literal_rounded(literal, RoundingMode::NearestEven)
+            }
+
+            /// Convert a literal to this format with the given rounding mode.
+            /// Returns an error if the literal is too large.
+            pub fn from_literal_rounded(
+                literal: FloatLiteral,
+                mode: RoundingMode,
+            ) -> Result<ConversionResult<$name>, OverflowError> {
+                $name::from_literal_with_flags(literal, mode).map(Conversion::result)
+            }
+
+            /// Convert a literal to this format with the given rounding mode
+            /// and report the exception flags. Returns an error if the literal
+            /// is too large, whatever the rounding mode.
+            pub fn from_literal_with_flags(
+                literal: FloatLiteral,
+                mode: RoundingMode,
+            ) -> Result<Conversion<$name>, OverflowError> {
+                let conversion = $format.convert_bits(&literal, mode);
+                if conversion.flags.overflow {
+                    return Err(OverflowError);
+                }
+                let words = (core::mem::size_of::<$bits_type>() / 2) as u32;
+                Ok(conversion.map(|bits| $name(swap_words(bits, words) as $bits_type)))
+            }
+        }
+    };
+}
+
+/// A VAX F_floating number, stored as its bits. This has a sign bit, an 8-bit
+/// exponent with a bias of 128 and a 24-bit fraction with a hidden leading bit.
+///
+/// VAX hardware rounds ties away from zero, which is
+/// `RoundingMode::NearestAway`.
+///
+/// ```rust
+/// use hexponent::{FloatLiteral, VaxF};
+/// let literal: FloatLiteral = "0x1".parse().unwrap();
+/// let one = VaxF::from_literal(literal).unwrap().inner();
+/// assert_eq!(one.to_bits(), 0x0000_4080);
+///
+/// let literal: FloatLiteral = "0x1p127".parse().unwrap();
+/// assert!(VaxF::from_literal(literal).is_err());
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VaxF(u32);
+
+/// A VAX D_floating number, stored as its bits. This has the same exponent as
+/// `VaxF`, and a 56-bit fraction.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VaxD(u64);
+
+/// A VAX G_floating number, stored as its bits. This has an 11-bit exponent
+/// with a bias of 1024 and a 53-bit fraction.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VaxG(u64);
+
+vax_type!(VaxF, u32, VAX_F);
+vax_type!(VaxD, u64, VAX_D);
+vax_type!(VaxG, u64, VAX_G);